@@ -52,6 +52,10 @@ class MeaningCodeGenerator
       build_pattern_match_branch(impl, functions)
     end
 
+    node_name_branches = impls.map do |impl|
+      %Q{prism::Node::#{impl[:name]} { .. } => "#{impl[:name]}",}
+    end
+
     autogen_source = <<~RUST
       // NOTE: This is auto-generated by scripts/meaning_autogen.rb
       impl super::Meaning {
@@ -61,6 +65,12 @@ class MeaningCodeGenerator
               }
           }
       }
+
+      pub(crate) fn node_name(node: &prism::Node) -> &'static str {
+          match node {
+              #{node_name_branches.join("\n")}
+          }
+      }
     RUST
 
     File.write(@output_path, autogen_source)
@@ -154,7 +164,7 @@ class MeaningCodeGenerator
         <<~RUST
           prism::Node::#{impl[:name]} { .. } => {
               let node = node.as_#{snaked_name}().unwrap();
-              self.start_node("#{impl[:name]}");
+              self.start_node("#{impl[:name]}", node.location());
               #{fields.join("\n    ")}
               self.end_node();
           }
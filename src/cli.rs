@@ -1,176 +1,657 @@
 use std::{
+    cell::Cell,
+    collections::HashMap,
     ffi::OsStr,
     io::{Read, Write},
+    ops::RangeInclusive,
     os::unix::prelude::OsStrExt,
-    path::PathBuf,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Mutex, Once,
+    },
+    thread,
 };
 
 use anyhow::Context;
 
-use crate::{config, error::AppError};
+use crate::{
+    config,
+    error::{AppError, Diagnostic},
+};
+
+mod git;
+mod lsp;
+mod server;
+mod targets;
 
 const VERSION: &str = "0.0.2";
 
+/// The outcome of a successful CLI run, which determines the process exit code.
+///
+/// Exit codes:
+/// - 0: All files were processed successfully.
+/// - 1: Some files could not be formatted (e.g. due to syntax errors).
+/// - 2: An internal error occurred (e.g. an I/O error or an invalid config). See [`INTERNAL_ERROR_EXIT_CODE`].
+/// - 3: Some files are not formatted yet (only reported in check mode).
+///
+/// If some files failed and others are unformatted, the failure takes precedence.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExitStatus {
+    Success,
+    Failed,
+    Unformatted,
+}
+
+pub const INTERNAL_ERROR_EXIT_CODE: u8 = 2;
+
+impl ExitStatus {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Success => 0,
+            Self::Failed => 1,
+            Self::Unformatted => 3,
+        }
+    }
+}
+
 #[derive(Debug)]
 enum Action {
     Print(String),
     Format(FormatRequest),
+    Lsp,
+    Server(crate::FormatOptions),
+    PrintConfig(PathBuf),
 }
 
 #[derive(Debug)]
 struct FormatRequest {
     write_to_file: bool,
+    check: bool,
+    diff: bool,
+    jobs: usize,
+    force_exclude: bool,
+    show_warnings: bool,
+    list_verbatim: bool,
+    verify: bool,
+    verify_idempotent: bool,
+    // The lines to format, if formatting only a part of the file.
+    lines: Option<RangeInclusive<usize>>,
+    // The git revision to format only the lines changed since.
+    changed_since: Option<String>,
     target: FormatTarget,
 }
 
+// The lines to format in each file. Files not in it are formatted entirely.
+type LineRanges = HashMap<PathBuf, Vec<RangeInclusive<usize>>>;
+
 #[derive(Debug)]
 enum FormatTarget {
-    Files { paths: Vec<String> },
-    Stdin,
+    Files {
+        paths: Vec<String>,
+    },
+    Stdin {
+        // The path of the file the source comes from, used to resolve config and in messages.
+        filepath: Option<String>,
+    },
 }
 
 pub fn run(
     r: &mut impl Read,
     w: &mut impl Write,
+    ew: &mut impl Write,
     args: impl IntoIterator<Item = impl AsRef<OsStr>>,
-) -> Result<(), anyhow::Error> {
+) -> Result<ExitStatus, anyhow::Error> {
     let action = parse_args(args)?;
     match action {
         Action::Print(message) => {
             writeln!(w, "{}", message.trim())?;
-            Ok(())
+            Ok(ExitStatus::Success)
         }
-        Action::Format(request) => run_format(r, w, request),
+        Action::Format(request) => run_format(r, w, ew, request),
+        Action::Lsp => lsp::serve(r, w),
+        Action::Server(options) => server::serve(r, w, options),
+        Action::PrintConfig(path) => print_config(w, &path),
     }
 }
 
+// Prints the effective config for the path in YAML, commenting the file each value comes from.
+fn print_config(w: &mut impl Write, path: &Path) -> Result<ExitStatus, anyhow::Error> {
+    if !path.exists() {
+        let message = format!("file not exist: {}", path.to_string_lossy());
+        return Err(AppError::Misc(message).into());
+    }
+    let resolved = match path.parent() {
+        _ if path.is_dir() => config::resolve_config_of_dir(path)?,
+        Some(parent) if !parent.as_os_str().is_empty() => config::resolve_config_of_path(path)?,
+        _ => config::resolve_config_of_dir(Path::new("."))?.for_file(path)?,
+    };
+    let value = serde_yaml::to_value(&resolved.config)?;
+    write_config_value(w, &value, "", &resolved.origins)?;
+    Ok(ExitStatus::Success)
+}
+
+fn write_config_value(
+    w: &mut impl Write,
+    value: &serde_yaml::Value,
+    key_path: &str,
+    origins: &HashMap<String, config::ConfigOrigin>,
+) -> Result<(), anyhow::Error> {
+    let serde_yaml::Value::Mapping(map) = value else {
+        return Ok(());
+    };
+    let indent = "  ".repeat(key_path.split('.').filter(|s| !s.is_empty()).count());
+    for (key, value) in map {
+        let key = key.as_str().unwrap_or_default();
+        let path = if key_path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{key_path}.{key}")
+        };
+        if value.is_mapping() {
+            writeln!(w, "{indent}{key}:")?;
+            write_config_value(w, value, &path, origins)?;
+        } else {
+            let origin = origins
+                .get(&path)
+                .map_or("default".to_string(), ToString::to_string);
+            // JSON is valid YAML and writes lists in a line.
+            let value = serde_json::to_string(value)?;
+            writeln!(w, "{indent}{key}: {value} # {origin}")?;
+        }
+    }
+    Ok(())
+}
+
 fn run_format(
     r: &mut impl Read,
     w: &mut impl Write,
+    ew: &mut impl Write,
     request: FormatRequest,
-) -> Result<(), anyhow::Error> {
+) -> Result<ExitStatus, anyhow::Error> {
     match request.target {
-        FormatTarget::Stdin => {
+        FormatTarget::Stdin { ref filepath } => {
             let mut source = Vec::new();
             r.read_to_end(&mut source)
                 .context("failed to read source")?;
-            let cwd = std::env::current_dir()?;
-            let config = config::config_of_dir(&cwd)?;
-            let result = crate::format_source(source, config.format)?;
-            write!(w, "{}", result.code)?;
-            if let Some(diff) = result.meaning_diff {
-                std::fs::write("stdin.rbfmt-before", diff.0)?;
-                std::fs::write("stdin.rbfmt-after", diff.1)?;
-                eprintln!("WARNING: code meaning changes detected");
+            let (name, config) = match filepath {
+                Some(filepath) => {
+                    let path = Path::new(filepath);
+                    if request.force_exclude && targets::is_excluded_file(path)? {
+                        // Output the source as is so that editors can handle excluded files in the same way.
+                        if !request.check && !request.diff {
+                            w.write_all(&source)?;
+                        }
+                        return Ok(ExitStatus::Success);
+                    }
+                    (filepath.as_str(), config::config_of_path(path)?)
+                }
+                None => {
+                    let cwd = std::env::current_dir()?;
+                    ("<stdin>", stdin_config(&cwd)?)
+                }
+            };
+            let options = crate::FormatOptions {
+                verify: request.verify || config.verify,
+                verify_idempotent: request.verify_idempotent,
+                comment_check: config.comment_check,
+            };
+            let lines = request.lines.clone().map(|lines| vec![lines]);
+            let result = match format_source_safely(source.clone(), config.format, options, lines) {
+                Ok(result) => result,
+                Err(err) => {
+                    write_format_error(ew, name, &err.with_path(name))?;
+                    return Ok(ExitStatus::Failed);
+                }
+            };
+            if request.show_warnings {
+                write_diagnostics(ew, name, &result.warnings)?;
+            }
+            write_diagnostics(ew, name, &result.check_warnings)?;
+            if request.list_verbatim {
+                write_diagnostics(ew, name, &result.verbatim_nodes)?;
+            }
+            let changed = result.code != source;
+            if request.diff {
+                if changed {
+                    write_diff(w, name, &source, &result.code)?;
+                }
+            } else if !request.check {
+                w.write_all(&result.code)?;
             }
-            Ok(())
+            if request.check && changed {
+                writeln!(ew, "Unformatted: {name}")?;
+                return Ok(ExitStatus::Unformatted);
+            }
+            Ok(ExitStatus::Success)
         }
         FormatTarget::Files { ref paths } => {
-            let target_paths =
-                flatten_target_paths(paths).context("failed to collect target file paths")?;
-            let need_file_separator = paths.len() > 1;
-            for path in target_paths {
-                let source = std::fs::read(&path).with_context(|| {
-                    let path = String::from_utf8_lossy(path.as_os_str().as_bytes());
-                    format!("failed to read target file: {path}")
-                })?;
-                let config = config::config_of_path(&path)?;
-                let result = crate::format_source(source, config.format);
-                match result {
-                    Ok(result) => {
-                        if let Some(diff) = result.meaning_diff {
-                            let path = path.as_os_str().to_string_lossy();
-                            std::fs::write(format!("{path}.rbfmt-before"), diff.0)?;
-                            std::fs::write(format!("{path}.rbfmt-after"), diff.1)?;
-                            eprintln!("WARNING: code meaning changes detected: {path}");
-                        }
-                        if request.write_to_file {
-                            std::fs::write(&path, result.code)?;
-                        } else {
-                            if need_file_separator {
-                                writeln!(w, "\n------ {:?} -----", &path)?;
-                            }
-                            write!(w, "{}", result.code)?;
-                        }
-                    }
-                    Err(err) => {
-                        writeln!(w, "failed to format {:?}", &path)?;
-                        writeln!(w, "error: {err}")?;
+            let (target_paths, line_ranges) = match &request.changed_since {
+                Some(rev) => targets::changed_target_paths(rev, paths)
+                    .context("failed to collect changed file paths")?,
+                None => {
+                    let target_paths = targets::flatten_target_paths(paths, request.force_exclude)
+                        .context("failed to collect target file paths")?;
+                    let mut line_ranges = HashMap::new();
+                    if let Some(lines) = &request.lines {
+                        let [path] = &target_paths[..] else {
+                            let message = "--lines can be used only for a single file".to_string();
+                            return Err(AppError::Misc(message).into());
+                        };
+                        line_ranges.insert(path.clone(), vec![lines.clone()]);
                     }
+                    (target_paths, line_ranges)
+                }
+            };
+            let need_file_separator = paths.len() > 1 || request.changed_since.is_some();
+            let mut unformatted_count = 0;
+            let mut failed_count = 0;
+            let options = crate::FormatOptions {
+                verify: request.verify,
+                verify_idempotent: request.verify_idempotent,
+                ..Default::default()
+            };
+            let jobs = request.jobs;
+            format_files_in_parallel(&target_paths, jobs, options, &line_ranges, |formatted| {
+                match write_formatted_file(w, ew, &request, need_file_separator, formatted)? {
+                    ExitStatus::Failed => failed_count += 1,
+                    ExitStatus::Unformatted => unformatted_count += 1,
+                    ExitStatus::Success => {}
                 }
+                Ok(())
+            })?;
+
+            if failed_count > 0 || request.check {
+                let mut summary = format!(
+                    "{} files processed, {failed_count} failed",
+                    target_paths.len()
+                );
+                if request.check {
+                    summary.push_str(&format!(", {unformatted_count} unformatted"));
+                }
+                writeln!(ew, "{summary}")?;
+            }
+            if failed_count > 0 {
+                Ok(ExitStatus::Failed)
+            } else if unformatted_count > 0 {
+                Ok(ExitStatus::Unformatted)
+            } else {
+                Ok(ExitStatus::Success)
             }
-            Ok(())
         }
     }
 }
 
-fn flatten_target_paths(target_paths: &Vec<String>) -> Result<Vec<PathBuf>, anyhow::Error> {
-    let mut paths = vec![];
-    for path in target_paths {
-        let path = PathBuf::from(path);
-        append_paths_recursively(path, &mut paths, false)?;
+// The source from stdin without its path is regarded as a Ruby file in the directory,
+// so that the `.editorconfig` sections for Ruby files apply to it.
+fn stdin_config(dir: &Path) -> Result<config::Config, anyhow::Error> {
+    let resolved = config::resolve_config_of_dir(dir)?;
+    Ok(resolved.for_file(&dir.join("stdin.rb"))?.config)
+}
+
+// Outputs the result of a file in the way requested and returns the status of the file.
+// A file failed to format is never written.
+fn write_formatted_file(
+    w: &mut impl Write,
+    ew: &mut impl Write,
+    request: &FormatRequest,
+    need_file_separator: bool,
+    formatted: FormattedFile,
+) -> Result<ExitStatus, anyhow::Error> {
+    let FormattedFile {
+        path,
+        source,
+        result,
+    } = formatted;
+    let result = match result {
+        Ok(result) => result,
+        Err(err) => {
+            write_format_error(ew, &path.to_string_lossy(), &err)?;
+            return Ok(ExitStatus::Failed);
+        }
+    };
+    if request.show_warnings {
+        write_diagnostics(ew, &path.to_string_lossy(), &result.warnings)?;
+    }
+    write_diagnostics(ew, &path.to_string_lossy(), &result.check_warnings)?;
+    if request.list_verbatim {
+        write_diagnostics(ew, &path.to_string_lossy(), &result.verbatim_nodes)?;
+    }
+    let changed = result.code != source;
+    if request.check && changed {
+        let path = path.as_os_str().to_string_lossy();
+        writeln!(ew, "Unformatted: {path}")?;
+    }
+    if request.diff {
+        if changed {
+            let path = path.as_os_str().to_string_lossy();
+            write_diff(w, &path, &source, &result.code)?;
+        }
+    } else if request.write_to_file {
+        if !request.check {
+            std::fs::write(path, result.code)?;
+        }
+    } else if !request.check {
+        if need_file_separator {
+            writeln!(w, "\n------ {:?} -----", path)?;
+        }
+        w.write_all(&result.code)?;
+    }
+    if request.check && changed {
+        Ok(ExitStatus::Unformatted)
+    } else {
+        Ok(ExitStatus::Success)
     }
-    Ok(paths)
 }
 
-fn append_paths_recursively(
-    path: PathBuf,
-    paths: &mut Vec<PathBuf>,
-    ignore_non_existiing_path: bool,
-) -> Result<(), anyhow::Error> {
-    if !path.exists() {
-        if ignore_non_existiing_path {
-            return Ok(());
-        } else {
-            let message = format!("file not exist: {}", path.as_os_str().to_string_lossy());
-            return Err(AppError::Misc(message).into());
+// Catch panics caused by formatter bugs so that a file never stops the others from being processed.
+fn format_source_safely(
+    source: Vec<u8>,
+    config: config::FormatConfig,
+    options: crate::FormatOptions,
+    lines: Option<Vec<RangeInclusive<usize>>>,
+) -> Result<crate::FormatResult, AppError> {
+    catch_panic(|| match lines {
+        Some(lines) => crate::format_lines(source, config, options, &lines),
+        None => crate::format_source(source, config, options),
+    })
+}
+
+thread_local! {
+    // Whether a panic in the thread is caught and reported as an error.
+    static CATCHING_PANIC: Cell<bool> = const { Cell::new(false) };
+}
+
+fn catch_panic<T>(f: impl FnOnce() -> Result<T, AppError>) -> Result<T, AppError> {
+    // The panic hook is global, so it is installed once and keeps quiet only while catching
+    // so that the default one does not print the panic with the error reported for it.
+    static QUIET_HOOK: Once = Once::new();
+    QUIET_HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING_PANIC.get() {
+                default_hook(info);
+            }
+        }));
+    });
+    CATCHING_PANIC.set(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING_PANIC.set(false);
+    result.unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown error".to_string());
+        Err(AppError::Misc(format!("unexpected panic: {message}")))
+    })
+}
+
+struct FormattedFile<'a> {
+    path: &'a Path,
+    source: Vec<u8>,
+    result: Result<crate::FormatResult, AppError>,
+}
+
+// A file that cannot be read or has an invalid config fails by itself
+// so that the other files are still formatted.
+fn format_file<'a>(
+    path: &'a Path,
+    configs: &DirConfigs,
+    options: crate::FormatOptions,
+    lines: Option<Vec<RangeInclusive<usize>>>,
+) -> FormattedFile<'a> {
+    let failed = |err: anyhow::Error| {
+        let err = match err.downcast::<AppError>() {
+            Ok(err) => err,
+            Err(err) => AppError::Misc(format!("{err:#}")),
+        };
+        FormattedFile {
+            path,
+            source: vec![],
+            result: Err(err),
+        }
+    };
+    let source = match std::fs::read(path) {
+        Ok(source) => source,
+        Err(err) => {
+            let path = String::from_utf8_lossy(path.as_os_str().as_bytes());
+            return failed(anyhow::anyhow!("failed to read target file: {path}: {err}"));
         }
+    };
+    let config = match configs.config_of_path(path) {
+        Ok(config) => config,
+        Err(err) => return failed(err),
+    };
+    let options = crate::FormatOptions {
+        verify: options.verify || config.verify,
+        comment_check: config.comment_check,
+        ..options
+    };
+    let result = format_source_safely(source.clone(), config.format, options, lines)
+        .map_err(|err| err.with_path(&path.to_string_lossy()));
+    FormattedFile {
+        path,
+        source,
+        result,
     }
-    if path.is_file() {
-        if let Some(ext) = path.extension() {
-            if ext == "rb" {
-                paths.push(path);
+}
+
+/// The resolved configs by directory, shared among workers so that config files are read once.
+#[derive(Debug, Default)]
+struct DirConfigs {
+    configs: Mutex<HashMap<PathBuf, config::ResolvedConfig>>,
+}
+
+impl DirConfigs {
+    fn config_of_path(&self, path: &Path) -> Result<config::Config, anyhow::Error> {
+        let Some(dir) = path.parent() else {
+            return config::config_of_path(path);
+        };
+        let cached = self.configs.lock().unwrap().get(dir).cloned();
+        let resolved = match cached {
+            Some(resolved) => resolved,
+            None => {
+                // Another worker may resolve the same directory meanwhile, which is harmless.
+                let resolved = config::resolve_config_of_dir(dir)?;
+                let mut configs = self.configs.lock().unwrap();
+                configs.insert(dir.to_path_buf(), resolved.clone());
+                resolved
             }
+        };
+        Ok(resolved.for_file(path)?.config)
+    }
+}
+
+// The formatting may recurse deeply for a large file so give workers a stack as large as the main thread.
+const WORKER_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Formats the given files using `jobs` worker threads.
+/// The results are passed to `f` one by one in the same order as `paths`,
+/// so the output stays deterministic regardless of the number of jobs.
+/// Files in `line_ranges` are formatted only on the lines.
+fn format_files_in_parallel<'a>(
+    paths: &'a [PathBuf],
+    jobs: usize,
+    options: crate::FormatOptions,
+    line_ranges: &LineRanges,
+    mut f: impl FnMut(FormattedFile<'a>) -> Result<(), anyhow::Error>,
+) -> Result<(), anyhow::Error> {
+    let next_index = AtomicUsize::new(0);
+    let configs = DirConfigs::default();
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(paths.len()) {
+            let sender = sender.clone();
+            let next_index = &next_index;
+            let configs = &configs;
+            thread::Builder::new()
+                .stack_size(WORKER_STACK_SIZE)
+                .spawn_scoped(scope, move || loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    let lines = line_ranges.get(path).cloned();
+                    // Stop working if the receiver has gone due to an error.
+                    if sender
+                        .send((index, format_file(path, configs, options, lines)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                })
+                .context("failed to spawn a worker thread")?;
         }
-    } else if path.is_dir() {
-        let entries = std::fs::read_dir(path)?;
-        for entry in entries {
-            let path = entry?.path();
-            append_paths_recursively(path, paths, true)?;
+        drop(sender);
+
+        let mut pending = HashMap::new();
+        let mut next_output_index = 0;
+        for (index, formatted) in receiver {
+            pending.insert(index, formatted);
+            while let Some(formatted) = pending.remove(&next_output_index) {
+                next_output_index += 1;
+                f(formatted)?;
+            }
         }
+        Ok(())
+    })
+}
+
+fn write_format_error(
+    ew: &mut impl Write,
+    name: &str,
+    err: &AppError,
+) -> Result<(), anyhow::Error> {
+    writeln!(ew, "failed to format {name}")?;
+    match err {
+        // The diagnostics are rendered with their severity.
+        AppError::ParseFailed(_) => write!(ew, "{err}")?,
+        AppError::InvalidConfig(_) => writeln!(ew, "{err}")?,
+        _ => writeln!(ew, "error: {err}")?,
     }
     Ok(())
 }
 
+fn write_diagnostics(
+    ew: &mut impl Write,
+    name: &str,
+    diagnostics: &[Diagnostic],
+) -> Result<(), anyhow::Error> {
+    for diagnostic in diagnostics {
+        let mut diagnostic = diagnostic.clone();
+        if diagnostic.path.is_none() {
+            diagnostic.path = Some(name.to_string());
+        }
+        writeln!(ew, "{diagnostic}\n")?;
+    }
+    Ok(())
+}
+
+fn write_diff(
+    w: &mut impl Write,
+    name: &str,
+    source: &[u8],
+    formatted: &[u8],
+) -> Result<(), anyhow::Error> {
+    let source = String::from_utf8_lossy(source);
+    let formatted = String::from_utf8_lossy(formatted);
+    let diff = similar::TextDiff::from_lines(source.as_ref(), formatted.as_ref());
+    let original_header = format!("a/{name}");
+    let formatted_header = format!("b/{name}");
+    let unified = diff
+        .unified_diff()
+        .context_radius(3)
+        .header(&original_header, &formatted_header)
+        .to_string();
+    write!(w, "{unified}")?;
+    Ok(())
+}
+
 const USAGE_FOOTER: &str = "
 To configure formatting, put .rbfmt.yml file.
+Files in directories are skipped if excluded by the config, .rbfmtignore, or .gitignore.
+Run `rbfmt lsp` to start a language server for editors on STDIN/STDOUT.
 ref: https://github.com/ryym/rbfmt
 ";
 
 fn parse_args(args: impl IntoIterator<Item = impl AsRef<OsStr>>) -> Result<Action, anyhow::Error> {
-    let args = args.into_iter();
+    let args = args
+        .into_iter()
+        .map(|arg| arg.as_ref().to_os_string())
+        .collect::<Vec<_>>();
+    if args.first().is_some_and(|arg| arg == "lsp") {
+        return Ok(Action::Lsp);
+    }
     let options = build_options();
 
     let matches = options.parse(args)?;
     if matches.opt_present("v") {
         return Ok(Action::Print(VERSION.to_string()));
     }
-    if matches.opt_present("h") || matches.free.is_empty() {
+    if matches.opt_present("print-config") {
+        let path = matches.free.first().map_or(".", String::as_str);
+        return Ok(Action::PrintConfig(PathBuf::from(path)));
+    }
+    if matches.opt_present("server") {
+        let options = crate::FormatOptions {
+            verify: matches.opt_present("verify"),
+            verify_idempotent: matches.opt_present("verify-idempotent"),
+            ..Default::default()
+        };
+        return Ok(Action::Server(options));
+    }
+    if matches.opt_present("h")
+        || (matches.free.is_empty()
+            && !matches.opt_present("stdin-filepath")
+            && !matches.opt_present("changed-since"))
+    {
         let mut usage = options.usage("Usage: rbfmt [options] [path/-]...");
         usage.push_str(USAGE_FOOTER);
         return Ok(Action::Print(usage));
     }
 
-    #[cfg(feature = "safety")]
     if matches.opt_present("__print-meaning") {
-        let target = matches.free.iter().next().unwrap();
+        let target = matches.free.first().unwrap();
         let result = crate::extract_meaning(target)?;
         return Ok(Action::Print(result));
     }
 
     let write_to_file = matches.opt_present("w");
-    let target = if matches.free.iter().any(|s| s == "-") {
-        FormatTarget::Stdin
+    let check = matches.opt_present("check");
+    let diff = matches.opt_present("diff");
+    let force_exclude = matches.opt_present("force-exclude");
+    let show_warnings = matches.opt_present("warnings");
+    let list_verbatim = matches.opt_present("list-verbatim");
+    let verify = matches.opt_present("verify");
+    let verify_idempotent = matches.opt_present("verify-idempotent");
+    let jobs = match matches.opt_get::<usize>("jobs")? {
+        Some(0) => {
+            let message = "the number of jobs must be greater than 0".to_string();
+            return Err(AppError::Misc(message).into());
+        }
+        Some(jobs) => jobs,
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let lines = matches
+        .opt_str("lines")
+        .map(|lines| parse_line_range(&lines))
+        .transpose()?;
+    let changed_since = matches.opt_str("changed-since");
+    if lines.is_some() && changed_since.is_some() {
+        let message = "--lines and --changed-since cannot be used together".to_string();
+        return Err(AppError::Misc(message).into());
+    }
+    let stdin_filepath = matches.opt_str("stdin-filepath");
+    let target = if stdin_filepath.is_some() || matches.free.iter().any(|s| s == "-") {
+        if changed_since.is_some() {
+            let message = "--changed-since cannot be used for STDIN".to_string();
+            return Err(AppError::Misc(message).into());
+        }
+        FormatTarget::Stdin {
+            filepath: stdin_filepath,
+        }
     } else {
         FormatTarget::Files {
             paths: matches.free,
@@ -179,18 +660,104 @@ fn parse_args(args: impl IntoIterator<Item = impl AsRef<OsStr>>) -> Result<Actio
 
     let fmt_request = FormatRequest {
         write_to_file,
+        check,
+        diff,
+        jobs,
+        force_exclude,
+        show_warnings,
+        list_verbatim,
+        verify,
+        verify_idempotent,
+        lines,
+        changed_since,
         target,
     };
     Ok(Action::Format(fmt_request))
 }
 
+// Parses a line range like `10:20`. Both ends are 1-based and inclusive.
+fn parse_line_range(value: &str) -> Result<RangeInclusive<usize>, AppError> {
+    let invalid = || AppError::Misc(format!("invalid line range: {value} (expected START:END)"));
+    let (start, end) = value.split_once(':').ok_or_else(invalid)?;
+    let start = start.parse::<usize>().map_err(|_| invalid())?;
+    let end = end.parse::<usize>().map_err(|_| invalid())?;
+    if start == 0 || start > end {
+        return Err(invalid());
+    }
+    Ok(start..=end)
+}
+
 fn build_options() -> getopts::Options {
     let mut o = getopts::Options::new();
     o.optflag("h", "help", "Print this help message");
     o.optflag("w", "write", "Write output to files instead of STDOUT");
+    o.optflag(
+        "",
+        "check",
+        "Do not output formatted code but list unformatted files and exit with non-zero status",
+    );
+    o.optflag(
+        "",
+        "diff",
+        "Print a unified diff between the source and the formatted code instead",
+    );
+    o.optopt(
+        "j",
+        "jobs",
+        "Number of files formatted in parallel (default: number of CPUs)",
+        "N",
+    );
+    o.optflag(
+        "",
+        "force-exclude",
+        "Apply the exclusion rules to files passed explicitly and --stdin-filepath as well",
+    );
+    o.optflag("", "warnings", "Print warnings reported by the parser");
+    o.optflag(
+        "",
+        "list-verbatim",
+        "Print the locations of code left unformatted because its syntax is not supported",
+    );
+    o.optopt(
+        "",
+        "stdin-filepath",
+        "Read source from STDIN and format it as if it came from the path",
+        "PATH",
+    );
+    o.optopt(
+        "",
+        "lines",
+        "Format only the statements on the lines and keep the rest of the file as it is",
+        "START:END",
+    );
+    o.optopt(
+        "",
+        "changed-since",
+        "Format only the lines changed since the git revision, in the paths if given",
+        "REV",
+    );
+    o.optflag(
+        "",
+        "verify",
+        "Fail instead of writing the result if formatting changes the meaning of the code",
+    );
+    o.optflag(
+        "",
+        "verify-idempotent",
+        "Fail instead of writing the result if formatting it again changes it",
+    );
+    o.optflag(
+        "",
+        "server",
+        "Keep running and format sources sent as JSON lines from STDIN, responding to STDOUT",
+    );
+    o.optflag(
+        "",
+        "print-config",
+        "Print the effective config for the path (default: .) and the file each value comes from",
+    );
     o.optflag("v", "version", "Print version");
 
-    #[cfg(feature = "safety")]
     o.optflag(
         "",
         "__print-meaning",
@@ -209,7 +776,12 @@ mod test {
     #[test]
     fn print_version() -> Result<(), Box<dyn Error>> {
         let mut output = Vec::new();
-        super::run(&mut std::io::empty(), &mut output, ["-v"])?;
+        super::run(
+            &mut std::io::empty(),
+            &mut output,
+            &mut std::io::sink(),
+            ["-v"],
+        )?;
 
         let output = String::from_utf8(output)?.to_string();
         assert_eq!(output, format!("{}\n", super::VERSION));
@@ -219,21 +791,512 @@ mod test {
     #[test]
     fn print_help_when_no_args_provided() -> Result<(), Box<dyn Error>> {
         let mut output = Vec::new();
-        super::run(&mut std::io::empty(), &mut output, [] as [&str; 0])?;
+        super::run(
+            &mut std::io::empty(),
+            &mut output,
+            &mut std::io::sink(),
+            [] as [&str; 0],
+        )?;
 
         let output = String::from_utf8(output)?.to_string();
         assert!(output.starts_with("Usage:"));
         Ok(())
     }
 
+    #[test]
+    fn keep_output_order_of_files_formatted_in_parallel() -> Result<(), Box<dyn Error>> {
+        // Every third file fails to format.
+        let unsupported = "# shareable_constant_value: literal\nA = [<<~X]; foo\n  a\nX\n";
+        let sources = (0..10)
+            .map(|i| match i % 3 {
+                2 => (format!("{i}.rb"), unsupported.to_string()),
+                _ => (format!("{i}.rb"), format!("foo(  {i})\n")),
+            })
+            .collect::<Vec<_>>();
+        let files = sources
+            .iter()
+            .map(|(path, source)| (path.as_str(), source.as_str()))
+            .collect::<Vec<_>>();
+        let files = crate::test::TempFiles::new("parallel", &files);
+        let paths = sources
+            .iter()
+            .map(|(path, _)| files.path(path))
+            .collect::<Vec<_>>();
+
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let args = ["--jobs", "4"]
+            .map(std::ffi::OsStr::new)
+            .into_iter()
+            .chain(paths.iter().map(|p| p.as_os_str()));
+        let status = super::run(&mut std::io::empty(), &mut output, &mut err_output, args)?;
+
+        assert_eq!(status.exit_code(), 1);
+        let want = paths
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i % 3 != 2)
+            .map(|(i, path)| format!("\n------ {path:?} -----\nfoo({i})\n"))
+            .collect::<String>();
+        assert_eq!(String::from_utf8(output)?, want);
+        let mut want = paths
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i % 3 == 2)
+            .map(|(_, path)| {
+                let path = path.display();
+                format!("failed to format {path}\nerror: unsupported syntax: ShareableConstantNode at {path}:2:1\n")
+            })
+            .collect::<String>();
+        want.push_str("10 files processed, 3 failed\n");
+        assert_eq!(String::from_utf8(err_output)?, want);
+        Ok(())
+    }
+
+    #[test]
+    fn fail_only_file_unreadable_or_with_invalid_config() -> Result<(), Box<dyn Error>> {
+        let files = crate::test::TempFiles::new(
+            "per-file-failure",
+            &[
+                ("a.rb", "foo(  1)\n"),
+                ("sub/a.rb", "foo\n"),
+                ("sub/.rbfmt.yml", "format:\n  line_widht: 1\n"),
+            ],
+        );
+        let configs = super::DirConfigs::default();
+        let options = crate::FormatOptions::default();
+
+        let path = files.path("a.rb");
+        let formatted = super::format_file(&path, &configs, options, None);
+        assert_eq!(formatted.result?.code, b"foo(1)\n");
+
+        let path = files.path("missing.rb");
+        let formatted = super::format_file(&path, &configs, options, None);
+        let Err(err) = formatted.result else {
+            panic!("formatted unexpectedly");
+        };
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "failed to read target file: {}: No such file or directory (os error 2)",
+                path.display()
+            )
+        );
+
+        let path = files.path("sub/a.rb");
+        let formatted = super::format_file(&path, &configs, options, None);
+        let Err(err) = formatted.result else {
+            panic!("formatted unexpectedly");
+        };
+        let want = crate::config::config_of_path(&path).unwrap_err();
+        assert!(matches!(err, crate::AppError::InvalidConfig(_)));
+        assert_eq!(err.to_string(), want.to_string());
+        Ok(())
+    }
+
     #[test]
     fn read_source_from_input() -> Result<(), Box<dyn Error>> {
         let input = b"foo  . bar(1  ,2+3,  4 )";
         let mut output = Vec::new();
-        super::run(&mut &input[..], &mut output, ["-"])?;
+        super::run(&mut &input[..], &mut output, &mut std::io::sink(), ["-"])?;
+
+        let output = String::from_utf8(output)?.to_string();
+        assert_eq!(&output, "foo.bar(1, 2 + 3, 4)\n");
+        Ok(())
+    }
+
+    #[test]
+    fn verify_meaning_of_formatted_input() -> Result<(), Box<dyn Error>> {
+        let input = b"foo  . bar(1  ,2+3,  4 )";
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let status = super::run(
+            &mut &input[..],
+            &mut output,
+            &mut err_output,
+            ["--verify", "-"],
+        )?;
+
+        assert_eq!(status, super::ExitStatus::Success);
+        let output = String::from_utf8(output)?.to_string();
+        assert_eq!(&output, "foo.bar(1, 2 + 3, 4)\n");
+        assert!(err_output.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn verify_idempotency_of_formatted_input() -> Result<(), Box<dyn Error>> {
+        let input = b"foo  . bar(1  ,2+3,  4 )";
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let status = super::run(
+            &mut &input[..],
+            &mut output,
+            &mut err_output,
+            ["--verify-idempotent", "-"],
+        )?;
 
+        assert_eq!(status, super::ExitStatus::Success);
         let output = String::from_utf8(output)?.to_string();
         assert_eq!(&output, "foo.bar(1, 2 + 3, 4)\n");
+        assert!(err_output.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn check_unformatted_input() -> Result<(), Box<dyn Error>> {
+        let input = b"foo  . bar(1  ,2+3,  4 )";
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let status = super::run(
+            &mut &input[..],
+            &mut output,
+            &mut err_output,
+            ["--check", "-"],
+        )?;
+
+        assert_eq!(status, super::ExitStatus::Unformatted);
+        assert!(output.is_empty());
+        let err_output = String::from_utf8(err_output)?.to_string();
+        assert_eq!(&err_output, "Unformatted: <stdin>\n");
+        Ok(())
+    }
+
+    #[test]
+    fn check_formatted_input() -> Result<(), Box<dyn Error>> {
+        let input = b"foo.bar(1, 2 + 3, 4)\n";
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let status = super::run(
+            &mut &input[..],
+            &mut output,
+            &mut err_output,
+            ["--check", "-"],
+        )?;
+
+        assert_eq!(status, super::ExitStatus::Success);
+        assert!(output.is_empty());
+        assert!(err_output.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn print_diff_of_unformatted_input() -> Result<(), Box<dyn Error>> {
+        let input = b"a = 1\nfoo  . bar(1  ,2)\nb = 2\n";
+        let mut output = Vec::new();
+        super::run(
+            &mut &input[..],
+            &mut output,
+            &mut std::io::sink(),
+            ["--diff", "-"],
+        )?;
+
+        let output = String::from_utf8(output)?.to_string();
+        let want = "\
+--- a/<stdin>
++++ b/<stdin>
+@@ -1,3 +1,3 @@
+ a = 1
+-foo  . bar(1  ,2)
++foo.bar(1, 2)
+ b = 2
+";
+        assert_eq!(&output, want);
+        Ok(())
+    }
+
+    #[test]
+    fn print_nothing_as_diff_of_formatted_input() -> Result<(), Box<dyn Error>> {
+        let input = b"foo.bar(1, 2)\n";
+        let mut output = Vec::new();
+        super::run(
+            &mut &input[..],
+            &mut output,
+            &mut std::io::sink(),
+            ["--diff", "-"],
+        )?;
+
+        assert!(output.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn use_stdin_filepath_in_messages() -> Result<(), Box<dyn Error>> {
+        let input = b"foo  . bar(1  ,2)\n";
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let status = super::run(
+            &mut &input[..],
+            &mut output,
+            &mut err_output,
+            ["--check", "--stdin-filepath", "app/models/user.rb"],
+        )?;
+
+        assert_eq!(status, super::ExitStatus::Unformatted);
+        let err_output = String::from_utf8(err_output)?.to_string();
+        assert_eq!(&err_output, "Unformatted: app/models/user.rb\n");
+        Ok(())
+    }
+
+    #[test]
+    fn apply_editorconfig_to_stdin_without_path() -> Result<(), Box<dyn Error>> {
+        let files = crate::test::TempFiles::new(
+            "stdin-editorconfig",
+            &[
+                (".editorconfig", "root = true\n[*.rb]\nindent_size = 4\n"),
+                (".rbfmt.yml", "format: {line_width: 80}\n"),
+            ],
+        );
+        let config = super::stdin_config(&files.root)?;
+        assert_eq!(config.format.indent_size, 4);
+        assert_eq!(config.format.line_width, 80);
+        Ok(())
+    }
+
+    #[test]
+    fn exclude_stdin_filepath_only_if_forced() -> Result<(), Box<dyn Error>> {
+        let files = crate::test::TempFiles::new(
+            "stdin-exclude",
+            &[
+                (".rbfmt.yml", "exclude: [vendor/**]\n"),
+                ("vendor/a.rb", ""),
+            ],
+        );
+        let path = files.path("vendor/a.rb");
+        let input = b"foo(  1)\n";
+
+        let mut output = Vec::new();
+        let args = [std::ffi::OsStr::new("--stdin-filepath"), path.as_os_str()];
+        super::run(&mut &input[..], &mut output, &mut std::io::sink(), args)?;
+        assert_eq!(String::from_utf8(output)?, "foo(1)\n");
+
+        let mut output = Vec::new();
+        let args = [
+            std::ffi::OsStr::new("--force-exclude"),
+            std::ffi::OsStr::new("--stdin-filepath"),
+            path.as_os_str(),
+        ];
+        super::run(&mut &input[..], &mut output, &mut std::io::sink(), args)?;
+        assert_eq!(String::from_utf8(output)?, "foo(  1)\n");
+        Ok(())
+    }
+
+    #[test]
+    fn report_format_error_to_stderr() -> Result<(), Box<dyn Error>> {
+        let input = b"foo(";
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let status = super::run(&mut &input[..], &mut output, &mut err_output, ["-"])?;
+
+        assert_eq!(status, super::ExitStatus::Failed);
+        assert!(output.is_empty());
+        let err_output = String::from_utf8(err_output)?.to_string();
+        assert!(err_output.starts_with("failed to format <stdin>\nerror: "));
+        Ok(())
+    }
+
+    #[test]
+    fn report_syntax_error_with_source_line() -> Result<(), Box<dyn Error>> {
+        let files = crate::test::TempFiles::new("syntax-error", &[("a.rb", "a = 1\n  x = 08\n")]);
+        let path = files.path("a.rb");
+
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let args = [path.as_os_str()];
+        let status = super::run(&mut std::io::empty(), &mut output, &mut err_output, args)?;
+
+        assert_eq!(status, super::ExitStatus::Failed);
+        assert!(output.is_empty());
+        let want = format!(
+            "\
+failed to format {path}
+error: unexpected integer, expecting end-of-input
+ --> {path}:2:8
+  |
+2 |   x = 08
+  |        ^
+1 files processed, 1 failed
+",
+            path = path.display()
+        );
+        assert_eq!(String::from_utf8(err_output)?, want);
+        Ok(())
+    }
+
+    #[test]
+    fn print_parser_warnings() -> Result<(), Box<dyn Error>> {
+        let source = "def foo\n  a = 1\nend\n";
+        let files = crate::test::TempFiles::new("warnings", &[("a.rb", source)]);
+        let path = files.path("a.rb");
+
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let args = [std::ffi::OsStr::new("--warnings"), path.as_os_str()];
+        let status = super::run(&mut std::io::empty(), &mut output, &mut err_output, args)?;
+
+        assert_eq!(status, super::ExitStatus::Success);
+        assert_eq!(String::from_utf8(output)?, source);
+        let want = format!(
+            "\
+warning: assigned but unused variable - a
+ --> {path}:2:3
+  |
+2 |   a = 1
+  |   ^
+
+",
+            path = path.display()
+        );
+        assert_eq!(String::from_utf8(err_output)?, want);
+        Ok(())
+    }
+
+    #[test]
+    fn report_unsupported_syntax_to_stderr() -> Result<(), Box<dyn Error>> {
+        // A heredoc in a node left as it is cannot be followed by other code.
+        let input = b"# shareable_constant_value: literal\nA = [<<~X]; foo\n  a\nX\n";
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let status = super::run(&mut &input[..], &mut output, &mut err_output, ["-"])?;
+
+        assert_eq!(status, super::ExitStatus::Failed);
+        assert!(output.is_empty());
+        let err_output = String::from_utf8(err_output)?.to_string();
+        assert_eq!(
+            err_output,
+            "failed to format <stdin>\nerror: unsupported syntax: ShareableConstantNode at <stdin>:2:1\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn convert_panic_into_error() {
+        use crate::error::AppError;
+
+        let result: Result<(), _> = super::catch_panic(|| panic!("static message"));
+        match result {
+            Err(AppError::Misc(message)) => assert_eq!(message, "unexpected panic: static message"),
+            _ => unreachable!("panic must be an error: {result:?}"),
+        }
+        let result: Result<(), _> = super::catch_panic(|| panic!("formatted {}", "message"));
+        match result {
+            Err(AppError::Misc(message)) => {
+                assert_eq!(message, "unexpected panic: formatted message")
+            }
+            _ => unreachable!("panic must be an error: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn keep_file_failed_to_format_unchanged() -> Result<(), Box<dyn Error>> {
+        // Any error such as broken output leaves the file as it is.
+        let source = "# shareable_constant_value: literal\nA = [<<~X]; foo(  1)\n  a\nX\n";
+        let files = crate::test::TempFiles::new("write-failure", &[("a.rb", source)]);
+        let path = files.path("a.rb");
+
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let args = [std::ffi::OsStr::new("-w"), path.as_os_str()];
+        let status = super::run(&mut std::io::empty(), &mut output, &mut err_output, args)?;
+
+        assert_eq!(status.exit_code(), 1);
+        assert!(output.is_empty());
+        assert_eq!(std::fs::read_to_string(&path)?, source);
+        let err_output = String::from_utf8(err_output)?;
+        assert!(err_output.starts_with(&format!("failed to format {}\n", path.display())));
+        Ok(())
+    }
+
+    #[test]
+    fn refuse_to_write_file_whose_meaning_changed() -> Result<(), Box<dyn Error>> {
+        // The formatter does not change the meaning without a bug, so the divergence is forced.
+        let source = "foo(1,  2)\n";
+        let files = crate::test::TempFiles::new("meaning-changed", &[("a.rb", source)]);
+        let path = files.path("a.rb");
+        let super::Action::Format(request) = super::parse_args(["-w", path.to_str().unwrap()])?
+        else {
+            panic!("not a format request");
+        };
+
+        let code = b"foo(1, 3)\n";
+        let meaning = |code: &[u8]| crate::meaning::extract(&prism::parse(code).node());
+        let divergence =
+            crate::meaning::first_divergence(&meaning(source.as_bytes()), &meaning(code)).unwrap();
+        let err = crate::meaning_change_error(source.as_bytes(), code, divergence);
+        let formatted = super::FormattedFile {
+            path: &path,
+            source: source.as_bytes().to_vec(),
+            result: Err(err.with_path(&path.to_string_lossy())),
+        };
+
+        let mut output = Vec::new();
+        let mut err_output = Vec::new();
+        let status =
+            super::write_formatted_file(&mut output, &mut err_output, &request, false, formatted)?;
+
+        assert_eq!(status.exit_code(), 1);
+        assert!(output.is_empty());
+        assert_eq!(std::fs::read_to_string(&path)?, source);
+        let mut file_names = std::fs::read_dir(&files.root)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().to_string()))
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        file_names.sort();
+        assert_eq!(file_names, vec![".editorconfig", ".rbfmt.yml", "a.rb"]);
+        let want = format!(
+            "\
+failed to format {path}
+error: formatting changed the meaning of the code (formatter bug)
+first difference at: ProgramNode.statements > StatementsNode.body[0] > CallNode.arguments > ArgumentsNode.arguments[1] > IntegerNode
+note: original: [IntegerNode] 2
+ --> {path}:1:9
+  |
+1 | foo(1,  2)
+  |         ^
+
+note: formatted: [IntegerNode] 3
+ --> <formatted>:1:8
+  |
+1 | foo(1, 3)
+  |        ^
+",
+            path = path.display()
+        );
+        assert_eq!(String::from_utf8(err_output)?, want);
+        Ok(())
+    }
+
+    #[test]
+    fn print_config_with_origins() -> Result<(), Box<dyn Error>> {
+        let files = crate::test::TempFiles::new(
+            "print-config",
+            &[
+                (
+                    ".rbfmt.yml",
+                    "format:\n  line_width: 80\nexclude: [vendor/**]\n",
+                ),
+                ("app/a.rb", ""),
+            ],
+        );
+        let config_path = files.path(".rbfmt.yml");
+
+        let mut output = Vec::new();
+        let path = files.path("app/a.rb");
+        let args = [std::ffi::OsStr::new("--print-config"), path.as_os_str()];
+        super::run(
+            &mut std::io::empty(),
+            &mut output,
+            &mut std::io::sink(),
+            args,
+        )?;
+
+        let output = String::from_utf8(output)?;
+        let lines = output.lines().collect::<Vec<_>>();
+        let config_path = config_path.to_string_lossy();
+        assert_eq!(lines[0], "format:");
+        assert_eq!(lines[1], format!("  line_width: 80 # {config_path}"));
+        assert_eq!(lines[2], "  indent_size: 2 # default");
+        assert!(lines.contains(&format!("exclude: [\"vendor/**\"] # {config_path}").as_str()));
+        assert!(lines.contains(&"verify: false # default"));
         Ok(())
     }
 }
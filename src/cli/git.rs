@@ -0,0 +1,243 @@
+use std::{
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::error::AppError;
+
+/// A file changed in the working tree since a revision.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct ChangedFile {
+    pub path: PathBuf,
+    /// The 1-based changed lines in the current content, or None if the whole file is new.
+    pub lines: Option<Vec<RangeInclusive<usize>>>,
+}
+
+/// Lists the files changed in the working tree since the revision, including untracked ones,
+/// using the local git repository of the current directory.
+pub(super) fn changed_files(rev: &str) -> Result<Vec<ChangedFile>, anyhow::Error> {
+    let root = git(None, &["rev-parse", "--show-toplevel"])?;
+    let root = PathBuf::from(root.trim_end_matches('\n'));
+    let diff = git(
+        Some(&root),
+        &[
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--diff-filter=ACMR",
+            "--unified=0",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            rev,
+            "--",
+        ],
+    )?;
+    let untracked = git(
+        Some(&root),
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+
+    let mut files = parse_diff(&diff)
+        .into_iter()
+        .map(|(path, lines)| ChangedFile {
+            path: root.join(path),
+            lines: Some(lines),
+        })
+        .collect::<Vec<_>>();
+    files.extend(
+        untracked
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(|path| ChangedFile {
+                path: root.join(path),
+                lines: None,
+            }),
+    );
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String, anyhow::Error> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    // Print non-ASCII paths as they are instead of quoting them.
+    command.args(["-c", "core.quotePath=false"]).args(args);
+    let output = command
+        .output()
+        .map_err(|err| AppError::Misc(format!("failed to run git: {err}")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = format!("git {} failed: {}", args.join(" "), stderr.trim());
+        return Err(AppError::Misc(message).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Collects the changed lines of each file from a unified diff without context lines.
+// The paths are relative to the repository root.
+fn parse_diff(diff: &str) -> Vec<(String, Vec<RangeInclusive<usize>>)> {
+    let mut files: Vec<(String, Vec<RangeInclusive<usize>>)> = vec![];
+    // Only the header of each file has its path, since a line in hunks may look like it,
+    // such as an added line starting with `++ b/`.
+    let mut in_header = false;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            in_header = true;
+        } else if let Some(path) = line.strip_prefix("+++ ").filter(|_| in_header) {
+            // Git appends a tab to paths containing spaces.
+            let path = path.strip_suffix('\t').unwrap_or(path);
+            let path = match path.strip_prefix('"') {
+                Some(quoted) => unquote_path(quoted.strip_suffix('"').unwrap_or(quoted)),
+                None => path.to_string(),
+            };
+            if let Some(path) = path.strip_prefix("b/") {
+                files.push((path.to_string(), vec![]));
+            }
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            in_header = false;
+            let Some((_, lines)) = files.last_mut() else {
+                continue;
+            };
+            if let Some(range) = parse_hunk_header(header) {
+                lines.push(range);
+            }
+        }
+    }
+    // A file may have only mode changes.
+    files.retain(|(_, lines)| !lines.is_empty());
+    files
+}
+
+// Unescapes a path quoted in the C style, which git uses for paths with special characters.
+fn unquote_path(quoted: &str) -> String {
+    let mut bytes = vec![];
+    let mut chars = quoted.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let byte = match chars.next() {
+            Some('a') => 0x07,
+            Some('b') => 0x08,
+            Some('t') => b'\t',
+            Some('n') => b'\n',
+            Some('v') => 0x0b,
+            Some('f') => 0x0c,
+            Some('r') => b'\r',
+            // Other bytes are escaped as three octal digits.
+            Some(d @ '0'..='3') => {
+                let digits = [Some(d), chars.next(), chars.next()];
+                digits
+                    .iter()
+                    .map(|d| d.and_then(|d| d.to_digit(8)).unwrap_or(0) as u8)
+                    .fold(0, |byte, d| byte * 8 + d)
+            }
+            Some(ch) => ch as u8,
+            None => break,
+        };
+        bytes.push(byte);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// Parses the new side of a hunk header like `-10,2 +12,3 @@`.
+fn parse_hunk_header(header: &str) -> Option<RangeInclusive<usize>> {
+    let new_side = header.split(' ').find_map(|s| s.strip_prefix('+'))?;
+    let (start, count) = match new_side.split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (new_side.parse::<usize>().ok()?, 1),
+    };
+    if count == 0 {
+        // Lines were only deleted after the start line, so format the statements around the gap.
+        return Some(start.max(1)..=start + 1);
+    }
+    Some(start..=start + count - 1)
+}
+
+#[cfg(test)]
+mod test {
+    use similar_asserts::assert_eq;
+
+    #[test]
+    fn parse_changed_lines_of_diff() {
+        let diff = "\
+diff --git a/lib/a.rb b/lib/a.rb
+index 1111111..2222222 100644
+--- a/lib/a.rb
++++ b/lib/a.rb
+@@ -3 +3 @@ class A
+-  def  foo
++  def foo
+@@ -10,0 +11,3 @@ class A
++  def bar
++++ b/bar
++  end
+@@ -20,3 +23,0 @@ class A
+-  def baz
+-  end
+-
+diff --git a/b.rb b/b.rb
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/b.rb
+@@ -0,0 +1,2 @@
++foo
++bar
+diff --git a/bin/c b/bin/c
+old mode 100644
+new mode 100755
+";
+        let files = super::parse_diff(diff);
+        assert_eq!(
+            files,
+            vec![
+                ("lib/a.rb".to_string(), vec![3..=3, 11..=13, 23..=24]),
+                ("b.rb".to_string(), vec![1..=2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_quoted_paths_of_diff() {
+        let diff = concat!(
+            r#"diff --git "a/d\te\"f.rb" "b/d\te\"f.rb"
+index 1111111..2222222 100644
+--- "a/d\te\"f.rb"
++++ "b/d\te\"f.rb"
+@@ -1 +1 @@
+-foo
++bar
+diff --git "a/\303\251\\.rb" "b/\303\251\\.rb"
+index 3333333..4444444 100644
+--- "a/\303\251\\.rb"
++++ "b/\303\251\\.rb"
+@@ -2 +2 @@
+-foo
++bar
+"#,
+            // Paths with spaces end with a tab.
+            "diff --git a/g h.rb b/g h.rb\n",
+            "index 5555555..6666666 100644\n",
+            "--- a/g h.rb\t\n",
+            "+++ b/g h.rb\t\n",
+            "@@ -3 +3 @@\n",
+            "-foo\n",
+            "+bar\n",
+        );
+        let files = super::parse_diff(diff);
+        assert_eq!(
+            files,
+            vec![
+                ("d\te\"f.rb".to_string(), vec![1..=1]),
+                ("é\\.rb".to_string(), vec![2..=2]),
+                ("g h.rb".to_string(), vec![3..=3]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,551 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    io::{BufRead, BufReader, Read, Write},
+    ops::RangeInclusive,
+    os::unix::prelude::OsStrExt,
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use serde_json::{json, Value};
+
+use crate::{
+    config,
+    error::{AppError, Diagnostic, Severity},
+    parse,
+};
+
+use super::ExitStatus;
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#errorCodes
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INTERNAL_ERROR: i64 = -32603;
+
+// textDocumentSync: the client sends the whole text on every change.
+const TEXT_DOCUMENT_SYNC_FULL: u8 = 1;
+
+/// Runs a language server over the given streams until the client asks it to exit.
+/// Only formatting is provided, along with the syntax errors of the documents.
+pub(super) fn serve(r: &mut impl Read, w: &mut impl Write) -> Result<ExitStatus, anyhow::Error> {
+    let mut reader = BufReader::new(r);
+    let mut server = Server::default();
+    while let Some(message) = read_message(&mut reader)? {
+        let message: Message =
+            serde_json::from_slice(&message).context("received an invalid LSP message")?;
+        if message.method.as_deref() == Some("exit") {
+            break;
+        }
+        let outgoing = server.handle(message);
+        for message in outgoing {
+            write_message(w, &message)?;
+        }
+    }
+    // The client must request shutdown before exit.
+    if server.shutdown_requested {
+        Ok(ExitStatus::Success)
+    } else {
+        Ok(ExitStatus::Failed)
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Message {
+    id: Option<Value>,
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct TextDocumentIdentifier {
+    uri: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidOpenParams {
+    text_document: TextDocumentItem,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct TextDocumentItem {
+    uri: String,
+    text: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidChangeParams {
+    text_document: TextDocumentIdentifier,
+    content_changes: Vec<ContentChange>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ContentChange {
+    text: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentParams {
+    text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RangeFormattingParams {
+    text_document: TextDocumentIdentifier,
+    range: LspRange,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LspRange {
+    start: Position,
+    end: Position,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnTypeFormattingParams {
+    text_document: TextDocumentIdentifier,
+    position: Position,
+    ch: String,
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+struct Position {
+    line: usize,
+    character: usize,
+}
+
+#[derive(Debug, Default)]
+struct Server {
+    // The texts of the opened documents by URI.
+    documents: HashMap<String, String>,
+    shutdown_requested: bool,
+}
+
+// The result of a request: a value to respond or an error code with its message.
+type RequestResult = Result<Value, (i64, String)>;
+
+impl Server {
+    // Returns the messages to send back to the client.
+    fn handle(&mut self, message: Message) -> Vec<Value> {
+        let mut outgoing = vec![];
+        let Some(method) = message.method else {
+            // Ignore responses since we send no requests to the client.
+            return outgoing;
+        };
+        let result = match method.as_str() {
+            "initialize" => Ok(initialize_result()),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            "textDocument/didOpen" => parse_params(message.params).map(|p: DidOpenParams| {
+                let doc = p.text_document;
+                self.documents.insert(doc.uri.clone(), doc.text);
+                outgoing.extend(self.check_document(&doc.uri));
+                Value::Null
+            }),
+            "textDocument/didChange" => parse_params(message.params).map(|p: DidChangeParams| {
+                let uri = p.text_document.uri;
+                if let Some(change) = p.content_changes.into_iter().last() {
+                    self.documents.insert(uri.clone(), change.text);
+                }
+                outgoing.extend(self.check_document(&uri));
+                Value::Null
+            }),
+            "textDocument/didClose" => parse_params(message.params).map(|p: DocumentParams| {
+                let uri = p.text_document.uri;
+                self.documents.remove(&uri);
+                outgoing.push(diagnostics_notification(&uri, vec![]));
+                Value::Null
+            }),
+            "textDocument/formatting" => {
+                parse_params(message.params).and_then(|p: DocumentParams| {
+                    self.format_document(&p.text_document.uri, None, &mut outgoing)
+                })
+            }
+            "textDocument/rangeFormatting" => {
+                parse_params(message.params).and_then(|p: RangeFormattingParams| {
+                    let (start, end) = (p.range.start, p.range.end);
+                    // The range ending at the start of a line does not include the line.
+                    let last = if end.character == 0 && end.line > start.line {
+                        end.line
+                    } else {
+                        end.line + 1
+                    };
+                    let lines = start.line + 1..=last;
+                    self.format_document(&p.text_document.uri, Some(lines), &mut outgoing)
+                })
+            }
+            "textDocument/onTypeFormatting" => {
+                parse_params(message.params).and_then(|p: OnTypeFormattingParams| {
+                    let uri = &p.text_document.uri;
+                    let text = self.documents.get(uri).map_or("", String::as_str);
+                    // Format the statement on the line just finished.
+                    let line = match p.ch.as_str() {
+                        "\n" if p.position.line > 0 => p.position.line,
+                        "d" if ends_with_keyword(line_prefix(text, p.position), "end") => {
+                            p.position.line + 1
+                        }
+                        _ => return Ok(Value::Null),
+                    };
+                    self.format_document(uri, Some(line..=line), &mut outgoing)
+                })
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method: {method}"))),
+        };
+        // Notifications have no ID and must not be responded.
+        if let Some(id) = message.id {
+            let response = match result {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err((code, message)) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": code, "message": message },
+                }),
+            };
+            outgoing.insert(0, response);
+        }
+        outgoing
+    }
+
+    // Formats the document, or only the statements on the lines if given.
+    fn format_document(
+        &self,
+        uri: &str,
+        lines: Option<RangeInclusive<usize>>,
+        outgoing: &mut Vec<Value>,
+    ) -> RequestResult {
+        let Some(text) = self.documents.get(uri) else {
+            return Err((INVALID_PARAMS, format!("unknown document: {uri}")));
+        };
+        match format_text(uri, text, lines) {
+            Ok(code) => {
+                outgoing.push(diagnostics_notification(uri, vec![]));
+                if code == text.as_bytes() {
+                    return Ok(json!([]));
+                }
+                let new_text = String::from_utf8_lossy(&code);
+                let range = json!({ "start": position(0, 0), "end": end_position(text) });
+                Ok(json!([{ "range": range, "newText": new_text }]))
+            }
+            Err(err) => match err.downcast::<AppError>() {
+                // Syntax errors are shown as diagnostics rather than a failure of the request.
+                Ok(AppError::ParseFailed(diagnostics)) => {
+                    let diagnostics = diagnostics.iter().map(|d| lsp_diagnostic(text, d));
+                    outgoing.push(diagnostics_notification(uri, diagnostics.collect()));
+                    Ok(Value::Null)
+                }
+                Ok(err) => Err((INTERNAL_ERROR, err.to_string())),
+                Err(err) => Err((INTERNAL_ERROR, format!("{err:#}"))),
+            },
+        }
+    }
+
+    // Publish the syntax errors of the document, if any.
+    // This runs on every change, so it only parses the document without formatting it.
+    fn check_document(&self, uri: &str) -> Option<Value> {
+        let text = self.documents.get(uri)?;
+        let result = prism::parse(text.as_bytes());
+        let diagnostics = result
+            .errors()
+            .map(|e| parse::diagnostic_of(&result, Severity::Error, e))
+            .map(|d| lsp_diagnostic(text, &d))
+            .collect();
+        Some(diagnostics_notification(uri, diagnostics))
+    }
+}
+
+fn initialize_result() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": TEXT_DOCUMENT_SYNC_FULL,
+            "documentFormattingProvider": true,
+            "documentRangeFormattingProvider": true,
+            "documentOnTypeFormattingProvider": {
+                "firstTriggerCharacter": "\n",
+                // The last character of `end`.
+                "moreTriggerCharacter": ["d"],
+            },
+        },
+        "serverInfo": { "name": "rbfmt", "version": super::VERSION },
+    })
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(params).map_err(|err| (INVALID_PARAMS, err.to_string()))
+}
+
+// Formats the text with the config resolved from the path of the document.
+fn format_text(
+    uri: &str,
+    text: &str,
+    lines: Option<RangeInclusive<usize>>,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let config = match path_of_uri(uri) {
+        Some(path) => config::config_of_path(&path)?,
+        None => config::config_of_dir(&std::env::current_dir()?)?,
+    };
+    let options = crate::FormatOptions {
+        verify: config.verify,
+        comment_check: config.comment_check,
+        ..Default::default()
+    };
+    let source = text.as_bytes().to_vec();
+    let lines = lines.map(|lines| vec![lines]);
+    let result = super::format_source_safely(source, config.format, options, lines)?;
+    Ok(result.code)
+}
+
+// Converts a `file:` URI to a file path. Other schemes have no path.
+fn path_of_uri(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| {
+            let hex = std::str::from_utf8(hex).ok()?;
+            u8::from_str_radix(hex, 16).ok()
+        });
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(OsStr::from_bytes(&decoded)))
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn lsp_diagnostic(text: &str, diagnostic: &Diagnostic) -> Value {
+    let line = diagnostic.line - 1;
+    let line_text = text.split('\n').nth(line).unwrap_or("");
+    let start = utf16_len(line_text.chars().take(diagnostic.column - 1));
+    let width = utf16_len(
+        line_text
+            .chars()
+            .skip(diagnostic.column - 1)
+            .take(diagnostic.width()),
+    );
+    // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#diagnosticSeverity
+    let severity = match diagnostic.severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+        Severity::Note => 3,
+    };
+    json!({
+        "range": { "start": position(line, start), "end": position(line, start + width) },
+        "severity": severity,
+        "source": "rbfmt",
+        "message": diagnostic.message,
+    })
+}
+
+fn position(line: usize, character: usize) -> Value {
+    json!({ "line": line, "character": character })
+}
+
+// Positions are counted by UTF-16 code units in LSP.
+fn end_position(text: &str) -> Value {
+    let line = text.matches('\n').count();
+    let last_line = text.rsplit('\n').next().unwrap_or("");
+    position(line, utf16_len(last_line.chars()))
+}
+
+fn utf16_len(chars: impl Iterator<Item = char>) -> usize {
+    chars.map(char::len_utf16).sum()
+}
+
+// Returns the text of the line before the position.
+fn line_prefix(text: &str, pos: Position) -> &str {
+    let line = text.split('\n').nth(pos.line).unwrap_or("");
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= pos.character {
+            return &line[..i];
+        }
+        units += c.len_utf16();
+    }
+    line
+}
+
+// Checks if the text ends with the keyword as a whole word, like `  end` but not `append`.
+fn ends_with_keyword(text: &str, keyword: &str) -> bool {
+    match text.strip_suffix(keyword) {
+        Some(rest) => !rest.ends_with(|c: char| c.is_alphanumeric() || c == '_'),
+        None => false,
+    }
+}
+
+// Reads the content of a message framed by the base protocol headers.
+// Returns None at the end of the input.
+fn read_message(r: &mut impl BufRead) -> Result<Option<Vec<u8>>, anyhow::Error> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if r.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                let length = value
+                    .trim()
+                    .parse::<usize>()
+                    .with_context(|| format!("invalid Content-Length header: {}", value.trim()))?;
+                content_length = Some(length);
+            }
+        }
+    }
+    let Some(content_length) = content_length else {
+        let message = "LSP message has no Content-Length header".to_string();
+        return Err(AppError::Misc(message).into());
+    };
+    let mut content = vec![0; content_length];
+    r.read_exact(&mut content)?;
+    Ok(Some(content))
+}
+
+fn write_message(w: &mut impl Write, message: &Value) -> Result<(), anyhow::Error> {
+    let content = serde_json::to_string(message)?;
+    write!(w, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    w.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use serde_json::{json, Value};
+    use similar_asserts::assert_eq;
+
+    fn run_session(messages: &[Value]) -> Result<(super::ExitStatus, Vec<Value>), Box<dyn Error>> {
+        let mut input = Vec::new();
+        for message in messages {
+            super::write_message(&mut input, message)?;
+        }
+        let mut output = Vec::new();
+        let status = super::serve(&mut &input[..], &mut output)?;
+
+        let mut responses = Vec::new();
+        let mut reader = &output[..];
+        while let Some(content) = super::read_message(&mut reader)? {
+            responses.push(serde_json::from_slice(&content)?);
+        }
+        Ok((status, responses))
+    }
+
+    fn did_open(text: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": "untitled:a.rb", "languageId": "ruby", "version": 1, "text": text },
+            },
+        })
+    }
+
+    fn formatting(id: u64) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/formatting",
+            "params": {
+                "textDocument": { "uri": "untitled:a.rb" },
+                "options": { "tabSize": 2, "insertSpaces": true },
+            },
+        })
+    }
+
+    fn shutdown_and_exit() -> [Value; 2] {
+        [
+            json!({ "jsonrpc": "2.0", "id": 99, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ]
+    }
+
+    #[test]
+    fn format_whole_document() -> Result<(), Box<dyn Error>> {
+        let mut messages = vec![
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            did_open("foo  . bar(1  ,2)"),
+            formatting(2),
+        ];
+        messages.extend(shutdown_and_exit());
+        let (status, responses) = run_session(&messages)?;
+
+        assert_eq!(status, super::ExitStatus::Success);
+        let response = responses.iter().find(|r| r["id"] == 2).unwrap();
+        let want = json!([{
+            "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": 0, "character": 17 },
+            },
+            "newText": "foo.bar(1, 2)\n",
+        }]);
+        assert_eq!(response["result"], want);
+        Ok(())
+    }
+
+    #[test]
+    fn publish_syntax_errors_as_diagnostics() -> Result<(), Box<dyn Error>> {
+        let mut messages = vec![did_open("foo("), formatting(1)];
+        messages.extend(shutdown_and_exit());
+        let (_, responses) = run_session(&messages)?;
+
+        let response = responses.iter().find(|r| r["id"] == 1).unwrap();
+        assert_eq!(response["result"], Value::Null);
+        let notification = responses
+            .iter()
+            .find(|r| r["method"] == "textDocument/publishDiagnostics")
+            .unwrap();
+        let diagnostics = notification["params"]["diagnostics"].as_array().unwrap();
+        assert!(!diagnostics.is_empty());
+        assert_eq!(diagnostics[0]["severity"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn format_on_end_keyword_only() {
+        let pos = |line, character| super::Position { line, character };
+        let text = "if a\n  b\n  append\nend";
+        assert_eq!(super::line_prefix(text, pos(3, 3)), "end");
+        assert!(super::ends_with_keyword(
+            super::line_prefix(text, pos(3, 3)),
+            "end"
+        ));
+        assert!(!super::ends_with_keyword(
+            super::line_prefix(text, pos(2, 8)),
+            "end"
+        ));
+    }
+
+    #[test]
+    fn convert_file_uri_to_path() {
+        let path = super::path_of_uri("file:///home/a%20b/x.rb");
+        assert_eq!(path, Some("/home/a b/x.rb".into()));
+        assert_eq!(super::path_of_uri("untitled:x.rb"), None);
+    }
+}
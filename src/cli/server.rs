@@ -0,0 +1,223 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use crate::{
+    config,
+    error::{AppError, Diagnostic},
+};
+
+use super::ExitStatus;
+
+#[derive(Debug, serde::Deserialize)]
+struct Request {
+    id: Value,
+    source: String,
+    /// The path of the file the source comes from, used to resolve config and in messages.
+    path: Option<String>,
+    /// Config values that take precedence over the config file, in the same structure.
+    config_overrides: Option<Value>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Response {
+    id: Value,
+    /// The formatted code, or null if formatting failed.
+    code: Option<String>,
+    changed: bool,
+    errors: Vec<ResponseError>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ResponseError {
+    message: String,
+    /// The 1-based position of the problem, if it is located in the source.
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+impl ResponseError {
+    fn new(message: String) -> Self {
+        Self {
+            message,
+            line: None,
+            column: None,
+        }
+    }
+}
+
+impl From<&Diagnostic> for ResponseError {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        Self {
+            message: diagnostic.message.clone(),
+            line: Some(diagnostic.line),
+            column: Some(diagnostic.column),
+        }
+    }
+}
+
+/// Formats sources sent as newline-delimited JSON requests until the end of the input.
+/// Each request is answered with a JSON response in a line, in the order of the requests.
+pub(super) fn serve(
+    r: &mut impl Read,
+    w: &mut impl Write,
+    options: crate::FormatOptions,
+) -> Result<ExitStatus, anyhow::Error> {
+    let mut configs = ConfigCache::default();
+    for line in BufReader::new(r).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => format_request(request, &mut configs, options),
+            // Keep serving since the other requests may be valid.
+            Err(err) => Response {
+                id: Value::Null,
+                code: None,
+                changed: false,
+                errors: vec![ResponseError::new(format!("invalid request: {err}"))],
+            },
+        };
+        serde_json::to_writer(&mut *w, &response)?;
+        writeln!(w)?;
+        w.flush()?;
+    }
+    Ok(ExitStatus::Success)
+}
+
+fn format_request(
+    request: Request,
+    configs: &mut ConfigCache,
+    options: crate::FormatOptions,
+) -> Response {
+    let failed = |errors| Response {
+        id: request.id.clone(),
+        code: None,
+        changed: false,
+        errors,
+    };
+    let config = match configs.config_for(request.path.as_deref(), request.config_overrides) {
+        Ok(config) => config,
+        Err(err) => return failed(vec![ResponseError::new(format!("{err:#}"))]),
+    };
+    let options = crate::FormatOptions {
+        verify: options.verify || config.verify,
+        comment_check: config.comment_check,
+        ..options
+    };
+    let source = request.source.into_bytes();
+    match super::format_source_safely(source.clone(), config.format, options, None) {
+        Ok(result) => Response {
+            id: request.id,
+            changed: result.code != source,
+            code: Some(String::from_utf8_lossy(&result.code).into_owned()),
+            errors: vec![],
+        },
+        Err(AppError::ParseFailed(diagnostics)) => {
+            failed(diagnostics.iter().map(ResponseError::from).collect())
+        }
+        Err(err) => failed(vec![ResponseError::new(err.to_string())]),
+    }
+}
+
+// Resolving a config walks up directories and reads files, so the resolved configs are
+// kept while the server runs. Changes to config files are not reflected until restart.
+#[derive(Debug, Default)]
+struct ConfigCache {
+    configs: HashMap<PathBuf, config::ResolvedConfig>,
+}
+
+impl ConfigCache {
+    fn config_for(
+        &mut self,
+        path: Option<&str>,
+        overrides: Option<Value>,
+    ) -> Result<config::Config, anyhow::Error> {
+        let parent = path.and_then(|path| Path::new(path).parent());
+        let dir = match parent.filter(|dir| !dir.as_os_str().is_empty()) {
+            Some(dir) => dir.to_path_buf(),
+            None => std::env::current_dir()?,
+        };
+        let resolved = match self.configs.entry(dir) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let resolved = config::resolve_config_of_dir(entry.key())?;
+                entry.insert(resolved)
+            }
+        };
+        let config = match path {
+            Some(path) => resolved.for_file(Path::new(path))?.config,
+            None => resolved.config.clone(),
+        };
+        match overrides {
+            Some(overrides) => config::override_config(&config, serde_yaml::to_value(overrides)?),
+            None => Ok(config),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use serde_json::{json, Value};
+    use similar_asserts::assert_eq;
+
+    fn serve(input: &str) -> Result<Vec<Value>, Box<dyn Error>> {
+        let mut output = Vec::new();
+        super::serve(
+            &mut input.as_bytes(),
+            &mut output,
+            crate::FormatOptions::default(),
+        )?;
+        let responses = String::from_utf8(output)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        Ok(responses)
+    }
+
+    #[test]
+    fn respond_to_each_request() -> Result<(), Box<dyn Error>> {
+        let input = [
+            json!({ "id": 1, "source": "foo  . bar(1  ,2)\n", "path": "a.rb" }),
+            json!({ "id": "b", "source": "foo(" }),
+            json!({ "id": 3, "source": "foo.bar(1, 2)\n" }),
+        ]
+        .map(|request| request.to_string())
+        .join("\n");
+        let responses = serve(&input)?;
+
+        assert_eq!(responses.len(), 3);
+        assert_eq!(
+            responses[0],
+            json!({ "id": 1, "code": "foo.bar(1, 2)\n", "changed": true, "errors": [] })
+        );
+        assert_eq!(responses[1]["id"], "b");
+        assert_eq!(responses[1]["code"], Value::Null);
+        assert_eq!(responses[1]["errors"][0]["line"], 1);
+        assert_eq!(
+            responses[2],
+            json!({ "id": 3, "code": "foo.bar(1, 2)\n", "changed": false, "errors": [] })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn keep_serving_after_invalid_request() -> Result<(), Box<dyn Error>> {
+        let input = "{\"id\": 1}\n{\"id\": 2, \"source\": \"a\\n\"}\n";
+        let responses = serve(input)?;
+
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], Value::Null);
+        let message = responses[0]["errors"][0]["message"].as_str().unwrap();
+        assert!(message.starts_with("invalid request: "));
+        assert_eq!(responses[1]["changed"], false);
+        Ok(())
+    }
+}
@@ -0,0 +1,523 @@
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use anyhow::Context;
+
+use crate::{
+    config::{self, GLOB_MATCH_OPTIONS},
+    error::AppError,
+};
+
+use super::{git, LineRanges};
+
+const RBFMT_IGNORE_FILE_NAME: &str = ".rbfmtignore";
+const GIT_IGNORE_FILE_NAME: &str = ".gitignore";
+
+// Files recognized as Ruby code in addition to the ones matching `include` config.
+const RUBY_FILE_EXTENSIONS: &[&str] = &["rb", "rbi", "rake", "gemspec", "ru", "thor", "jbuilder"];
+const RUBY_FILE_NAMES: &[&str] = &[
+    "Gemfile",
+    "Rakefile",
+    "Guardfile",
+    "Brewfile",
+    "Capfile",
+    "Podfile",
+    "Vagrantfile",
+    "Thorfile",
+    "Dangerfile",
+    "Fastfile",
+    "Appfile",
+    "Steepfile",
+    "Berksfile",
+    "Puppetfile",
+    "Jarfile",
+    "Mavenfile",
+    "Buildfile",
+];
+
+// Collect target Ruby file paths from the given paths.
+// Ruby files are detected by their extensions, names, shebangs, and the `include` config.
+// While walking directories, paths excluded by the `exclude` config, `.rbfmtignore` files,
+// and `.gitignore` files (if `respect_gitignore` is enabled) are skipped.
+// Files passed explicitly are always collected unless `force_exclude` is true.
+pub(super) fn flatten_target_paths(
+    target_paths: &Vec<String>,
+    force_exclude: bool,
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let mut paths = vec![];
+    for path in target_paths {
+        let path = PathBuf::from(path);
+        if !path.exists() {
+            let message = format!("file not exist: {}", path.as_os_str().to_string_lossy());
+            return Err(AppError::Misc(message).into());
+        }
+        let abs_path = fs::canonicalize(&path)?;
+        if path.is_dir() {
+            // The filter has the ignore files in the directory, so it is not entered again.
+            let filter = TargetFilter::of_dir(&abs_path)?;
+            append_dir_entries(&path, &abs_path, &filter, &mut paths)?;
+        } else {
+            let dir_path = abs_path.parent().unwrap_or(&abs_path);
+            let filter = TargetFilter::of_dir(dir_path)?;
+            if force_exclude && filter.is_excluded_with_ancestors(&abs_path) {
+                continue;
+            }
+            if filter.is_target_file(&abs_path)? {
+                paths.push(path);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+// Collect the Ruby files changed since the git revision, with their changed lines.
+// The files are filtered in the same way as walking directories, and limited to the given paths
+// if any. Untracked files are not in `line_ranges` since they are entirely new.
+pub(super) fn changed_target_paths(
+    rev: &str,
+    target_paths: &[String],
+) -> Result<(Vec<PathBuf>, LineRanges), anyhow::Error> {
+    let cwd = fs::canonicalize(std::env::current_dir()?)?;
+    let scopes = target_paths
+        .iter()
+        .map(|path| fs::canonicalize(path).with_context(|| format!("file not exist: {path}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut paths = vec![];
+    let mut line_ranges = LineRanges::new();
+    for file in git::changed_files(rev)? {
+        // Deleted or renamed files may remain in the diff against the working tree.
+        if !file.path.is_file() {
+            continue;
+        }
+        let abs_path = fs::canonicalize(&file.path)?;
+        if !scopes.is_empty() && !scopes.iter().any(|scope| abs_path.starts_with(scope)) {
+            continue;
+        }
+        let dir_path = abs_path.parent().unwrap_or(&abs_path);
+        let filter = TargetFilter::of_dir(dir_path)?;
+        if filter.is_excluded_with_ancestors(&abs_path) || !filter.is_target_file(&abs_path)? {
+            continue;
+        }
+        let path = match abs_path.strip_prefix(&cwd) {
+            Ok(path) => path.to_path_buf(),
+            Err(_) => abs_path.clone(),
+        };
+        if let Some(lines) = file.lines {
+            line_ranges.insert(path.clone(), lines);
+        }
+        paths.push(path);
+    }
+    Ok((paths, line_ranges))
+}
+
+// Check if the file is excluded, regardless of whether it actually exists or not.
+pub(super) fn is_excluded_file(path: &Path) -> Result<bool, anyhow::Error> {
+    let abs_path = if path.exists() {
+        fs::canonicalize(path)?
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    let dir_path = abs_path.parent().unwrap_or(&abs_path);
+    let filter = TargetFilter::of_dir(dir_path)?;
+    Ok(filter.is_excluded_with_ancestors(&abs_path))
+}
+
+fn append_paths_recursively(
+    path: PathBuf,
+    abs_path: PathBuf,
+    filter: &TargetFilter,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), anyhow::Error> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let is_dir = path.is_dir();
+    if filter.is_excluded(&abs_path, is_dir) {
+        return Ok(());
+    }
+    if is_dir {
+        let sub_filter = filter.enter_dir(&abs_path)?;
+        let filter = sub_filter.as_ref().unwrap_or(filter);
+        append_dir_entries(&path, &abs_path, filter, paths)?;
+    } else if filter.is_target_file(&abs_path)? {
+        paths.push(path);
+    }
+    Ok(())
+}
+
+fn append_dir_entries(
+    path: &Path,
+    abs_path: &Path,
+    filter: &TargetFilter,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), anyhow::Error> {
+    let mut entries = fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.file_name()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for name in entries {
+        append_paths_recursively(path.join(&name), abs_path.join(&name), filter, paths)?;
+    }
+    Ok(())
+}
+
+fn is_ruby_file(path: &Path) -> bool {
+    match path.extension() {
+        Some(ext) => RUBY_FILE_EXTENSIONS.iter().any(|e| ext == *e),
+        None => {
+            let is_known_name = path
+                .file_name()
+                .is_some_and(|name| RUBY_FILE_NAMES.iter().any(|n| name == *n));
+            is_known_name || has_ruby_shebang(path)
+        }
+    }
+}
+
+// Check the shebang only for files without extension to avoid reading every file.
+// Files that cannot be read are not regarded as Ruby files.
+fn has_ruby_shebang(path: &Path) -> bool {
+    let mut head = [0; 128];
+    let Ok(mut file) = fs::File::open(path) else {
+        return false;
+    };
+    let Ok(len) = file.read(&mut head) else {
+        return false;
+    };
+    let head = &head[..len];
+    if !head.starts_with(b"#!") {
+        return false;
+    }
+    let first_line = head.split(|b| *b == b'\n').next().unwrap_or(head);
+    first_line.windows(4).any(|w| w == b"ruby")
+}
+
+/// Rules to determine which paths should be skipped.
+#[derive(Debug, Clone)]
+struct TargetFilter {
+    config_dir: Option<PathBuf>,
+    config_excludes: Rc<Vec<glob::Pattern>>,
+    config_includes: Rc<Vec<glob::Pattern>>,
+    respect_gitignore: bool,
+    ignore_files: Vec<Rc<IgnoreFile>>,
+}
+
+impl TargetFilter {
+    fn of_dir(dir_path: &Path) -> Result<Self, anyhow::Error> {
+        let config = config::config_of_dir(dir_path)?;
+        let mut filter = Self {
+            config_dir: None,
+            config_excludes: Rc::new(vec![]),
+            config_includes: Rc::new(vec![]),
+            respect_gitignore: false,
+            ignore_files: vec![],
+        };
+        filter.apply_config(config)?;
+        let mut dirs = dir_path.ancestors().collect::<Vec<_>>();
+        dirs.reverse();
+        for dir in dirs {
+            filter.ignore_files.extend(IgnoreFile::load_all_in(dir)?);
+        }
+        Ok(filter)
+    }
+
+    fn apply_config(&mut self, config: config::Config) -> Result<(), anyhow::Error> {
+        let excludes = parse_glob_patterns(&config.exclude, "exclude")?;
+        let includes = parse_glob_patterns(&config.include, "include")?;
+        self.config_dir = config.dir;
+        self.config_excludes = Rc::new(excludes);
+        self.config_includes = Rc::new(includes);
+        self.respect_gitignore = config.respect_gitignore;
+        Ok(())
+    }
+
+    // Returns a new filter if the directory has its own config file or ignore files.
+    fn enter_dir(&self, dir_path: &Path) -> Result<Option<Self>, anyhow::Error> {
+        let config = config::config_just_in_dir(dir_path)?;
+        let ignore_files = IgnoreFile::load_all_in(dir_path)?;
+        if config.is_none() && ignore_files.is_empty() {
+            return Ok(None);
+        }
+        let mut filter = self.clone();
+        if let Some(config) = config {
+            filter.apply_config(config)?;
+        }
+        filter.ignore_files.extend(ignore_files);
+        Ok(Some(filter))
+    }
+
+    fn is_excluded(&self, abs_path: &Path, is_dir: bool) -> bool {
+        if let Some(config_dir) = &self.config_dir {
+            if let Ok(relative_path) = abs_path.strip_prefix(config_dir) {
+                let matched = self
+                    .config_excludes
+                    .iter()
+                    .any(|p| p.matches_path_with(relative_path, GLOB_MATCH_OPTIONS));
+                if matched {
+                    return true;
+                }
+            }
+        }
+        let mut ignored = false;
+        for ignore_file in &self.ignore_files {
+            if ignore_file.is_git && !self.respect_gitignore {
+                continue;
+            }
+            if let Some(decision) = ignore_file.decide(abs_path, is_dir) {
+                ignored = decision;
+            }
+        }
+        ignored
+    }
+
+    fn is_target_file(&self, abs_path: &Path) -> Result<bool, anyhow::Error> {
+        // Skip special files such as FIFOs and sockets, which could block on reading.
+        if !fs::metadata(abs_path)?.is_file() {
+            return Ok(false);
+        }
+        if let Some(config_dir) = &self.config_dir {
+            if let Ok(relative_path) = abs_path.strip_prefix(config_dir) {
+                let matched = self
+                    .config_includes
+                    .iter()
+                    .any(|p| p.matches_path_with(relative_path, GLOB_MATCH_OPTIONS));
+                if matched {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(is_ruby_file(abs_path))
+    }
+
+    fn is_excluded_with_ancestors(&self, abs_path: &Path) -> bool {
+        if self.is_excluded(abs_path, false) {
+            return true;
+        }
+        abs_path
+            .ancestors()
+            .skip(1)
+            .any(|dir_path| self.is_excluded(dir_path, true))
+    }
+}
+
+fn parse_glob_patterns(
+    patterns: &[String],
+    key: &str,
+) -> Result<Vec<glob::Pattern>, anyhow::Error> {
+    patterns
+        .iter()
+        .map(|p| glob::Pattern::new(p).with_context(|| format!("invalid {key} pattern: {p}")))
+        .collect()
+}
+
+/// An ignore file that has the same format as `.gitignore`.
+#[derive(Debug)]
+struct IgnoreFile {
+    dir: PathBuf,
+    is_git: bool,
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug)]
+struct IgnoreRule {
+    pattern: glob::Pattern,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl IgnoreFile {
+    fn load_all_in(dir_path: &Path) -> Result<Vec<Rc<Self>>, anyhow::Error> {
+        let mut files = vec![];
+        for (name, is_git) in [
+            (GIT_IGNORE_FILE_NAME, true),
+            (RBFMT_IGNORE_FILE_NAME, false),
+        ] {
+            let path = dir_path.join(name);
+            if path.is_file() {
+                let content = fs::read_to_string(&path).with_context(|| {
+                    format!("failed to read ignore file: {}", path.to_string_lossy())
+                })?;
+                let rules = content.lines().filter_map(IgnoreRule::parse).collect();
+                files.push(Rc::new(Self {
+                    dir: dir_path.to_path_buf(),
+                    is_git,
+                    rules,
+                }));
+            }
+        }
+        Ok(files)
+    }
+
+    // Returns whether the path is ignored or not if any rule matches. Later rules take precedence.
+    fn decide(&self, abs_path: &Path, is_dir: bool) -> Option<bool> {
+        let relative_path = abs_path.strip_prefix(&self.dir).ok()?;
+        if relative_path.as_os_str().is_empty() {
+            return None;
+        }
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.dir_only)
+                    && rule
+                        .pattern
+                        .matches_path_with(relative_path, GLOB_MATCH_OPTIONS)
+            })
+            .map(|rule| !rule.negated)
+    }
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        // A pattern containing a slash is relative to the ignore file directory.
+        // Otherwise it matches a path at any depth.
+        let pattern = if let Some(rest) = line.strip_prefix('/') {
+            rest.to_string()
+        } else if line.contains('/') {
+            line.to_string()
+        } else {
+            format!("**/{line}")
+        };
+        let pattern = glob::Pattern::new(&pattern).ok()?;
+        Some(Self {
+            pattern,
+            negated,
+            dir_only,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use similar_asserts::assert_eq;
+
+    use crate::test::TempFiles;
+
+    fn collect(root: &Path, force_exclude: bool, paths: &[&str]) -> Vec<String> {
+        let paths = paths
+            .iter()
+            .map(|p| root.join(p).to_string_lossy().to_string())
+            .collect();
+        super::flatten_target_paths(&paths, force_exclude)
+            .unwrap()
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn skip_paths_excluded_by_config_and_ignore_files() {
+        let files = TempFiles::new(
+            "filter",
+            &[
+                (".rbfmt.yml", "exclude:\n  - vendor/**\n  - '**/*_pb.rb'\n"),
+                (".rbfmtignore", "tmp/\n!keep.rb\n*.gen.rb\n"),
+                (".gitignore", "log/\n"),
+                ("a.rb", ""),
+                ("b_pb.rb", ""),
+                ("vendor/bundle/c.rb", ""),
+                ("tmp/d.rb", ""),
+                ("lib/e.gen.rb", ""),
+                ("lib/keep.rb", ""),
+                ("log/f.rb", ""),
+            ],
+        );
+        let paths = collect(&files.root, false, &[""]);
+        assert_eq!(paths, vec!["a.rb", "lib/keep.rb", "log/f.rb"]);
+    }
+
+    #[test]
+    fn respect_gitignore_if_enabled() {
+        let files = TempFiles::new(
+            "gitignore",
+            &[
+                (".rbfmt.yml", "respect_gitignore: true\n"),
+                (".gitignore", "/log\n"),
+                ("a.rb", ""),
+                ("log/b.rb", ""),
+                ("sub/log/c.rb", ""),
+            ],
+        );
+        let paths = collect(&files.root, false, &[""]);
+        assert_eq!(paths, vec!["a.rb", "sub/log/c.rb"]);
+    }
+
+    #[test]
+    fn exclude_explicit_files_only_if_forced() {
+        let files = TempFiles::new(
+            "force-exclude",
+            &[
+                (".rbfmt.yml", "exclude:\n  - vendor/**\n"),
+                ("vendor/a.rb", ""),
+            ],
+        );
+        assert_eq!(
+            collect(&files.root, false, &["vendor/a.rb"]),
+            vec!["vendor/a.rb"]
+        );
+        assert_eq!(
+            collect(&files.root, true, &["vendor/a.rb"]),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn detect_ruby_files_without_rb_extension() {
+        let files = TempFiles::new(
+            "detection",
+            &[
+                (".rbfmt.yml", "include:\n  - '**/*.arb'\n"),
+                ("Gemfile", ""),
+                ("app.gemspec", ""),
+                ("config.ru", ""),
+                ("lib/tasks/a.rake", ""),
+                ("bin/run", "#!/usr/bin/env ruby\nputs 1\n"),
+                ("bin/setup", "#!/bin/sh\necho 1\n"),
+                ("views/a.arb", ""),
+                ("README.md", ""),
+            ],
+        );
+        let paths = collect(&files.root, false, &[""]);
+        assert_eq!(
+            paths,
+            vec![
+                "Gemfile",
+                "app.gemspec",
+                "bin/run",
+                "config.ru",
+                "lib/tasks/a.rake",
+                "views/a.arb",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skip_special_files() {
+        let files = TempFiles::new("special", &[("a.rb", ""), ("bin/.keep", "")]);
+        let _sockets = ["b.rb", "bin/run"]
+            .map(|path| std::os::unix::net::UnixListener::bind(files.path(path)).unwrap());
+        assert_eq!(collect(&files.root, false, &[""]), vec!["a.rb"]);
+        assert_eq!(
+            collect(&files.root, false, &["bin/run"]),
+            Vec::<String>::new()
+        );
+    }
+}
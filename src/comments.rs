@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::error::{Diagnostic, Severity};
+
+/// A comment with its text normalized so that it is comparable
+/// between the source and the formatted code.
+#[derive(Debug)]
+pub struct Comment {
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+pub fn extract(result: &prism::ParseResult) -> Vec<Comment> {
+    result
+        .comments()
+        .map(|comment| {
+            let loc = comment.location();
+            Comment {
+                text: normalize(loc.as_slice()),
+                start: loc.start_offset(),
+                end: loc.end_offset(),
+            }
+        })
+        .collect()
+}
+
+// Formatting may change the indentation and trailing spaces of comments.
+fn normalize(value: &[u8]) -> String {
+    String::from_utf8_lossy(value)
+        .lines()
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Compares the comments as multisets and reports each comment that
+/// was lost or appeared more times than in the source.
+pub fn compare(
+    source: &[u8],
+    before: &[Comment],
+    code: &[u8],
+    after: &[Comment],
+    severity: Severity,
+) -> Vec<Diagnostic> {
+    let (before_texts, before_groups) = group_by_text(before);
+    let (after_texts, after_groups) = group_by_text(after);
+    let mut diagnostics = vec![];
+
+    for text in before_texts {
+        let after_count = after_groups.get(text).map_or(0, Vec::len);
+        if let Some(lost) = before_groups[text].get(after_count) {
+            let message = "comment is lost by formatting".to_string();
+            let diagnostic = Diagnostic::new(severity, source, lost.start, lost.end, message);
+            diagnostics.push(diagnostic);
+        }
+    }
+    for text in after_texts {
+        let before_count = before_groups.get(text).map_or(0, Vec::len);
+        if let Some(extra) = after_groups[text].get(before_count) {
+            let message = "comment appears more times than in the source".to_string();
+            let mut diagnostic = Diagnostic::new(severity, code, extra.start, extra.end, message);
+            diagnostic.path = Some("<formatted>".to_string());
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+// Groups the comments by text. The texts are listed in the order of their first appearance.
+fn group_by_text(comments: &[Comment]) -> (Vec<&str>, HashMap<&str, Vec<&Comment>>) {
+    let mut texts = vec![];
+    let mut groups: HashMap<&str, Vec<&Comment>> = HashMap::new();
+    for comment in comments {
+        let text = comment.text.as_str();
+        let group = groups.entry(text).or_insert_with(|| {
+            texts.push(text);
+            vec![]
+        });
+        group.push(comment);
+    }
+    (texts, groups)
+}
+
+#[cfg(test)]
+mod test {
+    use similar_asserts::assert_eq;
+
+    use crate::error::Severity;
+
+    fn compare(before: &[u8], after: &[u8]) -> Vec<String> {
+        let before_comments = super::extract(&prism::parse(before));
+        let after_comments = super::extract(&prism::parse(after));
+        super::compare(
+            before,
+            &before_comments,
+            after,
+            &after_comments,
+            Severity::Error,
+        )
+        .iter()
+        .map(|d| {
+            let path = d.path.as_deref().unwrap_or("-");
+            format!("{path}:{}:{} {}", d.line, d.column, d.message)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn ignore_indentation_of_comments() {
+        let before = b"if a\n      # foo  \n  b\nend\n";
+        let after = b"if a\n  # foo\n  b\nend\n";
+        assert_eq!(compare(before, after), Vec::<String>::new());
+    }
+
+    #[test]
+    fn report_lost_and_duplicated_comments() {
+        let before = b"a # x\n# y\nb\n";
+        let after = b"a\n# y\n# y\nb\n";
+        let want = vec![
+            "-:1:3 comment is lost by formatting".to_string(),
+            "<formatted>:3:1 comment appears more times than in the source".to_string(),
+        ];
+        assert_eq!(compare(before, after), want);
+    }
+}
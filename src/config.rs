@@ -1,53 +1,749 @@
 use std::{
-    fs::File,
-    io::BufReader,
+    collections::HashMap,
+    fs,
+    ops::Range,
     os::unix::prelude::OsStrExt,
     path::{Path, PathBuf},
 };
 
 use anyhow::Context;
+use serde::Deserialize;
 
-#[derive(Debug, Default, serde::Deserialize)]
+use crate::error::{AppError, Diagnostic, Severity};
+
+use self::editorconfig::EditorConfig;
+
+mod editorconfig;
+
+pub const CONFIG_FILE_NAME: &str = ".rbfmt.yml";
+
+pub(crate) const GLOB_MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
     pub format: FormatConfig,
+    /// Glob patterns of paths to skip, relative to the directory of the config file.
+    pub exclude: Vec<String>,
+    /// Glob patterns of additional files to format, relative to the directory of the config file.
+    pub include: Vec<String>,
+    /// Whether to skip paths ignored by `.gitignore` files.
+    pub respect_gitignore: bool,
+    /// Whether to check that formatting does not change the meaning of the code.
+    pub verify: bool,
+    /// What to do when formatting loses or duplicates comments.
+    pub comment_check: CommentCheck,
+    /// Config files to inherit values from, relative to the directory of the config file.
+    #[serde(deserialize_with = "one_or_many", skip_serializing)]
+    pub inherit_from: Vec<String>,
+    #[serde(skip_serializing)]
+    pub overrides: Vec<ConfigOverride>,
+    /// The directory where the config file exists, if any.
+    #[serde(skip)]
+    pub dir: Option<PathBuf>,
+}
+
+/// Format values for the files matching the glob patterns, relative to the directory of the project config file.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigOverride {
+    pub files: Vec<String>,
+    /// Only the values written in the config file are applied over the others,
+    /// so they are kept as they are instead of filling in the defaults.
+    #[serde(default)]
+    pub format: serde_yaml::Value,
+}
+
+// Accepts a string as a list of it.
+fn one_or_many<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => Ok(vec![value]),
+        OneOrMany::Many(values) => Ok(values),
+    }
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentCheck {
+    /// Fail to format the file.
+    #[default]
+    Error,
+    /// Output the formatted code with warnings.
+    Warning,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct FormatConfig {
+    #[serde(deserialize_with = "positive")]
     pub line_width: usize,
+    /// The width of an indentation level.
+    #[serde(deserialize_with = "positive")]
+    pub indent_size: usize,
+    pub indent_style: IndentStyle,
+    /// The width a tab is counted as when fitting code in `line_width`. Defaults to `indent_size`.
+    #[serde(deserialize_with = "positive_option")]
+    pub tab_width: Option<usize>,
+    pub line_ending: LineEnding,
 }
 
 impl Default for FormatConfig {
     fn default() -> Self {
-        Self { line_width: 100 }
+        Self {
+            line_width: 100,
+            indent_size: 2,
+            indent_style: IndentStyle::Spaces,
+            tab_width: None,
+            line_ending: LineEnding::Auto,
+        }
     }
 }
 
-pub fn config_of_path(file_path: &Path) -> Result<Config, anyhow::Error> {
-    match file_path.parent() {
-        Some(dir_path) => config_of_dir(dir_path),
-        None => Ok(Config::default()),
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndentStyle {
+    #[default]
+    Spaces,
+    /// Indent each level by a tab.
+    Tabs,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    /// Use the line ending that most lines of the source end with.
+    #[default]
+    Auto,
+}
+
+impl LineEnding {
+    /// Resolves `Auto` to the dominant line ending of the source, preferring LF on a tie.
+    pub(crate) fn resolve(self, source: &[u8]) -> Self {
+        match self {
+            Self::Auto => {
+                let crlf = source.windows(2).filter(|w| w == b"\r\n").count();
+                let lf = source.iter().filter(|b| **b == b'\n').count() - crlf;
+                if crlf > lf {
+                    Self::Crlf
+                } else {
+                    Self::Lf
+                }
+            }
+            _ => self,
+        }
+    }
+}
+
+const NOT_POSITIVE: &str = "must be greater than 0";
+
+fn positive<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    let value = usize::deserialize(deserializer)?;
+    if value == 0 {
+        return Err(serde::de::Error::custom(NOT_POSITIVE));
+    }
+    Ok(value)
+}
+
+fn positive_option<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<usize>, D::Error> {
+    match Option::<usize>::deserialize(deserializer)? {
+        Some(0) => Err(serde::de::Error::custom(NOT_POSITIVE)),
+        value => Ok(value),
+    }
+}
+
+/// Where a config value comes from.
+#[derive(Debug, Clone)]
+pub enum ConfigOrigin {
+    File(PathBuf),
+    /// An item of `overrides` in the config file.
+    Override {
+        path: PathBuf,
+        index: usize,
+    },
+    /// A section of an `.editorconfig` file.
+    EditorConfig {
+        path: PathBuf,
+        section: String,
+    },
+}
+
+impl std::fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.to_string_lossy()),
+            Self::Override { path, index } => {
+                write!(f, "overrides[{index}] in {}", path.to_string_lossy())
+            }
+            Self::EditorConfig { path, section } => {
+                write!(f, "[{section}] in {}", path.to_string_lossy())
+            }
+        }
     }
 }
 
+/// The config of a directory with the config file each value comes from.
+#[derive(Debug, Default, Clone)]
+pub struct ResolvedConfig {
+    pub config: Config,
+    /// The origins of the values set by config files, keyed by their paths like `format.line_width`.
+    /// The values not in it are the defaults.
+    pub origins: HashMap<String, ConfigOrigin>,
+    overrides: Vec<OverrideRule>,
+    editorconfigs: Vec<EditorConfig>,
+}
+
+// An item of `overrides` with its patterns relative to the directory of the project config file.
+#[derive(Debug, Clone)]
+struct OverrideRule {
+    dir: PathBuf,
+    files: Vec<glob::Pattern>,
+    format: serde_yaml::Value,
+    origin: ConfigOrigin,
+}
+
+impl ResolvedConfig {
+    /// Returns the config for the file, applying the `.editorconfig` values for it under the values
+    /// in config files, and then the overrides whose patterns match it in order.
+    pub fn for_file(&self, file_path: &Path) -> Result<Self, anyhow::Error> {
+        let abs_path = absolute_path(file_path)?;
+        let mut resolved = self.clone();
+        for (key, value, origin) in editorconfig::format_values(&self.editorconfigs, &abs_path) {
+            if self.origins.contains_key(&format!("format.{key}")) {
+                continue;
+            }
+            let mut format = serde_yaml::Mapping::new();
+            format.insert(key.into(), value);
+            let mut value = serde_yaml::Mapping::new();
+            value.insert("format".into(), serde_yaml::Value::Mapping(format));
+            resolved.apply(serde_yaml::Value::Mapping(value), origin)?;
+        }
+        for rule in &self.overrides {
+            let Ok(relative_path) = abs_path.strip_prefix(&rule.dir) else {
+                continue;
+            };
+            let matched = rule
+                .files
+                .iter()
+                .any(|p| p.matches_path_with(relative_path, GLOB_MATCH_OPTIONS));
+            if matched {
+                let mut value = serde_yaml::Mapping::new();
+                value.insert("format".into(), rule.format.clone());
+                resolved.apply(serde_yaml::Value::Mapping(value), rule.origin.clone())?;
+            }
+        }
+        Ok(resolved)
+    }
+
+    // Loads the config file after the ones it inherits from, so that its own values take precedence.
+    // The patterns in every file are relative to the directory of the project config file,
+    // which is the first one in the inheritance chain.
+    fn load(
+        &mut self,
+        config_path: &Path,
+        inheriting: &mut Vec<PathBuf>,
+    ) -> Result<(), anyhow::Error> {
+        let mut value = read_config_file(config_path)?;
+        let canonical_path = fs::canonicalize(config_path)?;
+        if inheriting.contains(&canonical_path) {
+            let chain = inheriting
+                .iter()
+                .chain([&canonical_path])
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>();
+            let message = format!(
+                "config files inherit from each other: {}",
+                chain.join(" -> ")
+            );
+            return Err(AppError::Misc(message).into());
+        }
+        let config: Config = serde_yaml::from_value(value.clone())?;
+        let dir = canonical_path
+            .parent()
+            .unwrap_or(Path::new("/"))
+            .to_path_buf();
+        let project_dir = match inheriting.first() {
+            Some(project_path) => project_path
+                .parent()
+                .unwrap_or(Path::new("/"))
+                .to_path_buf(),
+            None => dir.clone(),
+        };
+
+        inheriting.push(canonical_path);
+        for parent_path in &config.inherit_from {
+            self.load(&dir.join(parent_path), inheriting)?;
+        }
+        inheriting.pop();
+
+        if let serde_yaml::Value::Mapping(map) = &mut value {
+            map.remove("inherit_from");
+            map.remove("overrides");
+        }
+        self.apply(value, ConfigOrigin::File(config_path.to_path_buf()))?;
+
+        for (index, item) in config.overrides.into_iter().enumerate() {
+            // The format is checked when the config file is read.
+            let format = match item.format {
+                serde_yaml::Value::Null => serde_yaml::Value::Mapping(Default::default()),
+                format => format,
+            };
+            self.overrides.push(OverrideRule {
+                dir: project_dir.clone(),
+                files: parse_glob_patterns(&item.files)?,
+                format,
+                origin: ConfigOrigin::Override {
+                    path: config_path.to_path_buf(),
+                    index,
+                },
+            });
+        }
+        Ok(())
+    }
+
+    // Merges the values into the config key by key.
+    fn apply(
+        &mut self,
+        value: serde_yaml::Value,
+        origin: ConfigOrigin,
+    ) -> Result<(), anyhow::Error> {
+        let mut keys = vec![];
+        collect_leaf_keys(&value, "", &mut keys);
+        for key in keys {
+            self.origins.insert(key, origin.clone());
+        }
+        self.config = override_config(&self.config, value)?;
+        Ok(())
+    }
+}
+
+fn parse_glob_patterns(patterns: &[String]) -> Result<Vec<glob::Pattern>, anyhow::Error> {
+    patterns
+        .iter()
+        .map(|p| glob::Pattern::new(p).with_context(|| format!("invalid overrides pattern: {p}")))
+        .collect()
+}
+
+fn collect_leaf_keys(value: &serde_yaml::Value, prefix: &str, keys: &mut Vec<String>) {
+    match value {
+        serde_yaml::Value::Mapping(map) => {
+            for (key, value) in map {
+                let key = key.as_str().unwrap_or_default();
+                let path = if prefix.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                collect_leaf_keys(value, &path, keys);
+            }
+        }
+        _ => keys.push(prefix.to_string()),
+    }
+}
+
+pub fn config_of_path(file_path: &Path) -> Result<Config, anyhow::Error> {
+    resolve_config_of_path(file_path).map(|resolved| resolved.config)
+}
+
+pub fn resolve_config_of_path(file_path: &Path) -> Result<ResolvedConfig, anyhow::Error> {
+    let resolved = match file_path.parent() {
+        Some(dir_path) => resolve_config_of_dir(dir_path)?,
+        None => ResolvedConfig::default(),
+    };
+    resolved.for_file(file_path)
+}
+
 pub fn config_of_dir(dir_path: &Path) -> Result<Config, anyhow::Error> {
-    let config_path = find_config_file_path(dir_path);
-    let config = match config_path {
-        Some(config_path) => {
-            let config_file = File::open(&config_path).with_context(|| {
-                let path = String::from_utf8_lossy(config_path.as_os_str().as_bytes());
-                format!("failed to open config file: {path}")
-            })?;
-            let reader = BufReader::new(config_file);
-            serde_yaml::from_reader(reader)?
+    resolve_config_of_dir(dir_path).map(|resolved| resolved.config)
+}
+
+/// Resolves the config of the nearest config file and the ones it inherits from,
+/// along with the `.editorconfig` files for the directory.
+/// The `overrides` and `.editorconfig` sections are not applied until the config is used for a file.
+pub fn resolve_config_of_dir(dir_path: &Path) -> Result<ResolvedConfig, anyhow::Error> {
+    let mut resolved = match find_config_file_path(dir_path) {
+        Some(config_path) => resolve_config_file(&config_path)?,
+        None => ResolvedConfig::default(),
+    };
+    resolved.editorconfigs = editorconfig::load_all(&absolute_path(dir_path)?)?;
+    Ok(resolved)
+}
+
+// Resolves symbolic links if the path exists, to match paths with the ones of config files.
+fn absolute_path(path: &Path) -> Result<PathBuf, anyhow::Error> {
+    let path =
+        fs::canonicalize(path).or_else(|_| std::env::current_dir().map(|cwd| cwd.join(path)))?;
+    Ok(path)
+}
+
+fn resolve_config_file(config_path: &Path) -> Result<ResolvedConfig, anyhow::Error> {
+    let mut resolved = ResolvedConfig::default();
+    resolved.load(config_path, &mut vec![])?;
+    resolved.config.dir = config_path.parent().map(Path::to_path_buf);
+    Ok(resolved)
+}
+
+/// Returns the config whose values are replaced by the given ones.
+/// The overrides have the same structure as the config file and are merged key by key.
+pub fn override_config(
+    config: &Config,
+    overrides: serde_yaml::Value,
+) -> Result<Config, anyhow::Error> {
+    let mut value = serde_yaml::to_value(config)?;
+    merge_values(&mut value, overrides);
+    let mut overridden: Config =
+        serde_yaml::from_value(value).context("invalid config overrides")?;
+    overridden.dir.clone_from(&config.dir);
+    Ok(overridden)
+}
+
+fn merge_values(base: &mut serde_yaml::Value, value: serde_yaml::Value) {
+    match (base, value) {
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(map)) => {
+            for (key, value) in map {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_values(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
+
+/// Loads the config file placed just in the given directory, without looking up its ancestors.
+pub fn config_just_in_dir(dir_path: &Path) -> Result<Option<Config>, anyhow::Error> {
+    let config_path = dir_path.join(CONFIG_FILE_NAME);
+    if config_path.exists() {
+        resolve_config_file(&config_path).map(|resolved| Some(resolved.config))
+    } else {
+        Ok(None)
+    }
+}
+
+// Reads the config file as a YAML value after checking its keys and values.
+fn read_config_file(config_path: &Path) -> Result<serde_yaml::Value, anyhow::Error> {
+    let source = fs::read_to_string(config_path).with_context(|| {
+        let path = String::from_utf8_lossy(config_path.as_os_str().as_bytes());
+        format!("failed to open config file: {path}")
+    })?;
+    let path = config_path.to_string_lossy();
+    parse_config_source(&path, &source)
+}
+
+fn parse_config_source(path: &str, source: &str) -> Result<serde_yaml::Value, anyhow::Error> {
+    let invalid_config = |mut diagnostic: Diagnostic| {
+        diagnostic.path = Some(path.to_string());
+        AppError::InvalidConfig(Box::new(diagnostic))
+    };
+    let value = match serde_yaml::from_str(source) {
+        // An empty file has no values.
+        Ok(serde_yaml::Value::Null) => serde_yaml::Value::Mapping(Default::default()),
+        Ok(value) => value,
+        Err(err) => return Err(invalid_config(config_diagnostic(source, err, None)).into()),
+    };
+    // The formats of overrides are kept as values in the config, so they are checked after the others.
+    let checked = match serde_yaml::from_str::<Config>(source) {
+        Ok(_) => serde_yaml::from_str::<OverrideFormats>(source).map_err(|err| (err, true)),
+        Err(err) => Err((err, false)),
+    };
+    if let Err((err, override_formats)) = checked {
+        let problem = find_config_problem(&value, override_formats);
+        return Err(invalid_config(config_diagnostic(source, err, problem)).into());
+    }
+    Ok(value)
+}
+
+// Deserialized only to check the formats of overrides, so that the errors have their locations.
+#[derive(serde::Deserialize)]
+struct OverrideFormats {
+    #[serde(default, rename = "overrides")]
+    _overrides: Vec<OverrideFormat>,
+}
+
+#[derive(serde::Deserialize)]
+struct OverrideFormat {
+    #[serde(default, rename = "format")]
+    _format: Option<FormatConfig>,
+}
+
+// The keys of the mappings in a config file.
+const CONFIG_KEYS: &[&str] = &[
+    "format",
+    "exclude",
+    "include",
+    "respect_gitignore",
+    "verify",
+    "comment_check",
+    "inherit_from",
+    "overrides",
+];
+const FORMAT_KEYS: &[&str] = &[
+    "line_width",
+    "indent_size",
+    "indent_style",
+    "tab_width",
+    "line_ending",
+];
+const OVERRIDE_KEYS: &[&str] = &["files", "format"];
+
+// The names the key takes as its value, if any.
+fn variants_of(key: &str) -> &'static [&'static str] {
+    match key {
+        "indent_style" => &["spaces", "tabs"],
+        "line_ending" => &["lf", "crlf", "auto"],
+        "comment_check" => &["error", "warning"],
+        _ => &[],
+    }
+}
+
+/// What is wrong with a value in a config file.
+#[derive(Debug)]
+enum ConfigProblem {
+    UnknownKey {
+        /// The path of the mapping the key is in, like `format`, or empty for the top level.
+        key_path: String,
+        name: String,
+        candidates: &'static [&'static str],
+    },
+    UnknownVariant {
+        key_path: String,
+        name: String,
+        candidates: &'static [&'static str],
+    },
+    InvalidValue {
+        /// The path of the value, or empty for the whole config.
+        key_path: String,
+        /// The key of the value, if it is checked by itself.
+        key: Option<String>,
+        detail: String,
+    },
+}
+
+// Finds the first problem in the config in the same order as deserializing it does,
+// either in the formats of overrides or in the other values.
+fn find_config_problem(value: &serde_yaml::Value, override_formats: bool) -> Option<ConfigProblem> {
+    if override_formats {
+        let overrides = value.get("overrides")?.as_sequence()?;
+        return overrides.iter().enumerate().find_map(|(i, item)| {
+            let format = item.get("format").filter(|format| !format.is_null())?;
+            let key_path = format!("overrides[{i}].format");
+            find_mapping_problem::<FormatConfig>(format, &key_path, FORMAT_KEYS, |_, _| None)
+        });
+    }
+    find_mapping_problem::<Config>(value, "", CONFIG_KEYS, |key, value| match key {
+        "format" => find_mapping_problem::<FormatConfig>(value, key, FORMAT_KEYS, |_, _| None),
+        "overrides" => value
+            .as_sequence()?
+            .iter()
+            .enumerate()
+            .find_map(|(i, item)| {
+                let key_path = format!("overrides[{i}]");
+                // The format can be any value here.
+                find_mapping_problem::<ConfigOverride>(item, &key_path, OVERRIDE_KEYS, |_, _| None)
+            }),
+        _ => None,
+    })
+}
+
+// Checks the keys of the mapping and then each value by deserializing it alone,
+// after the mappings nested in it are checked by `nested`, which is given the key and value.
+fn find_mapping_problem<T: serde::de::DeserializeOwned>(
+    value: &serde_yaml::Value,
+    key_path: &str,
+    keys: &'static [&'static str],
+    nested: impl Fn(&str, &serde_yaml::Value) -> Option<ConfigProblem>,
+) -> Option<ConfigProblem> {
+    let invalid_value = |value: serde_yaml::Value, key_path: String, key: Option<&str>| {
+        let err = serde_yaml::from_value::<T>(value).err()?;
+        Some(ConfigProblem::InvalidValue {
+            key_path,
+            key: key.map(str::to_string),
+            detail: err.to_string(),
+        })
+    };
+    let Some(map) = value.as_mapping() else {
+        return invalid_value(value.clone(), key_path.to_string(), None);
+    };
+    for (key, value) in map {
+        let mut single = serde_yaml::Mapping::new();
+        single.insert(key.clone(), value.clone());
+        let single = serde_yaml::Value::Mapping(single);
+        let Some(name) = key.as_str() else {
+            return invalid_value(single, key_path.to_string(), None);
+        };
+        if !keys.contains(&name) {
+            return Some(ConfigProblem::UnknownKey {
+                key_path: key_path.to_string(),
+                name: name.to_string(),
+                candidates: keys,
+            });
         }
-        None => Config::default(),
+        let value_path = match key_path {
+            "" => name.to_string(),
+            _ => format!("{key_path}.{name}"),
+        };
+        let candidates = variants_of(name);
+        match value.as_str() {
+            Some(variant) if !candidates.is_empty() && !candidates.contains(&variant) => {
+                return Some(ConfigProblem::UnknownVariant {
+                    key_path: value_path,
+                    name: variant.to_string(),
+                    candidates,
+                });
+            }
+            _ => {}
+        }
+        if let Some(problem) =
+            nested(name, value).or_else(|| invalid_value(single, value_path, Some(name)))
+        {
+            return Some(problem);
+        }
+    }
+    None
+}
+
+// Makes a diagnostic of the error of serde_yaml, pointing at the key or value with the problem
+// found in the config value. The error is only used for its location unless it is a syntax error.
+fn config_diagnostic(
+    source: &str,
+    err: serde_yaml::Error,
+    problem: Option<ConfigProblem>,
+) -> Diagnostic {
+    let start = err.location().map_or(0, |location| location.index());
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let in_key_path = |key_path: &str| match key_path {
+        "" => String::new(),
+        _ => format!(" in `{key_path}`"),
     };
-    Ok(config)
+    let (message, start, end) = match problem {
+        Some(ConfigProblem::UnknownKey {
+            key_path,
+            name,
+            candidates,
+        }) => {
+            let mut message = format!("unknown key `{name}`{}", in_key_path(&key_path));
+            append_suggestion(&mut message, &name, candidates);
+            (message, start, start + name.len())
+        }
+        Some(ConfigProblem::UnknownVariant {
+            key_path,
+            name,
+            candidates,
+        }) => {
+            let mut message = format!("invalid value `{name}` for `{key_path}`");
+            append_suggestion(&mut message, &name, candidates);
+            (message, start, start + name.len())
+        }
+        Some(ConfigProblem::InvalidValue {
+            key_path,
+            key,
+            detail,
+        }) => {
+            // Errors of our own checks are located at the key or the mapping, so point at the value.
+            let range = key
+                .and_then(|key| value_range_of_key(source, start, line_end, &key))
+                .unwrap_or(start..start + source[start..line_end].trim_end().len());
+            let message = match key_path.as_str() {
+                "" => detail,
+                _ => format!("invalid value for `{key_path}`: {detail}"),
+            };
+            (message, range.start, range.end)
+        }
+        None => {
+            // A syntax error tells its location at the end, which the diagnostic shows instead.
+            let mut message = err.to_string();
+            if let Some(location) = err.location() {
+                let suffix = format!(" at line {} column {}", location.line(), location.column());
+                message = message.replacen(&suffix, "", 1);
+            }
+            let end = start + source[start..line_end].trim_end().len();
+            (message, start, end)
+        }
+    };
+    Diagnostic::new(Severity::Error, source.as_bytes(), start, end, message)
+}
+
+// The range of the value of the key from the offset in the line, like `0` in `{line_width: 0}`.
+// The values checked by us are plain numbers, which end before a comma or brace in flow mappings.
+fn value_range_of_key(
+    source: &str,
+    offset: usize,
+    line_end: usize,
+    key: &str,
+) -> Option<Range<usize>> {
+    let line = &source[offset..line_end];
+    line.match_indices(key).find_map(|(i, _)| {
+        let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        if line[..i].ends_with(is_word) {
+            return None;
+        }
+        let rest = line[i + key.len()..].trim_start_matches([' ', '\t']);
+        let value = rest.strip_prefix(':')?.trim_start_matches([' ', '\t']);
+        let start = line_end - value.len();
+        let len = value.find([',', '}', '#']).unwrap_or(value.len());
+        Some(start..start + value[..len].trim_end().len())
+    })
+}
+
+fn append_suggestion(message: &mut String, name: &str, candidates: &[&str]) {
+    let closest = candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= candidate.len().max(3) / 3)
+        .min_by_key(|(distance, _)| *distance);
+    match closest {
+        Some((_, candidate)) => message.push_str(&format!(", did you mean `{candidate}`?")),
+        None if !candidates.is_empty() => {
+            let candidates = candidates
+                .iter()
+                .map(|c| format!("`{c}`"))
+                .collect::<Vec<_>>();
+            message.push_str(&format!(" (expected one of {})", candidates.join(", ")));
+        }
+        None => {}
+    }
+}
+
+// The Levenshtein distance between the strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                prev.min(row[j]).min(current) + 1
+            };
+            prev = current;
+        }
+    }
+    row[b.len()]
 }
 
 fn find_config_file_path(base: &Path) -> Option<PathBuf> {
-    let config_path = base.join(".rbfmt.yml");
+    let config_path = base.join(CONFIG_FILE_NAME);
     if config_path.exists() {
         return Some(config_path);
     }
@@ -56,3 +752,222 @@ fn find_config_file_path(base: &Path) -> Option<PathBuf> {
     }
     None
 }
+
+#[cfg(test)]
+mod test {
+    use similar_asserts::assert_eq;
+
+    use crate::test::TempFiles;
+
+    #[test]
+    fn override_config_key_by_key() -> Result<(), anyhow::Error> {
+        let config: super::Config = serde_yaml::from_str("format: {line_width: 80}\nverify: true")?;
+        let overrides = serde_yaml::from_str("format: {line_width: 120}")?;
+        let overridden = super::override_config(&config, overrides)?;
+
+        assert_eq!(overridden.format.line_width, 120);
+        assert!(overridden.verify);
+        Ok(())
+    }
+
+    #[test]
+    fn reject_unknown_key_with_suggestion() {
+        let source = "format:\n  line_widht: 120\n";
+        let err = super::parse_config_source(".rbfmt.yml", source).unwrap_err();
+        let want = "\
+error: unknown key `line_widht` in `format`, did you mean `line_width`?
+ --> .rbfmt.yml:2:3
+  |
+2 |   line_widht: 120
+  |   ^^^^^^^^^^";
+        assert_eq!(err.to_string(), want);
+    }
+
+    #[test]
+    fn reject_invalid_values() {
+        let cases = [
+            (
+                "verfy: true\nfoo: 1\n",
+                "unknown key `verfy`, did you mean `verify`?",
+                (1, 1),
+            ),
+            (
+                "format:\n  indent_style: tab\n",
+                "invalid value `tab` for `format.indent_style`, did you mean `tabs`?",
+                (2, 17),
+            ),
+            (
+                "format:\n  line_width: 0\n",
+                "invalid value for `format.line_width`: must be greater than 0",
+                (2, 15),
+            ),
+            (
+                "verify: yes\n",
+                "invalid value for `verify`: invalid type: string \"yes\", expected a boolean",
+                (1, 9),
+            ),
+            (
+                "format: {line_width: 0}\n",
+                "invalid value for `format.line_width`: must be greater than 0",
+                (1, 22),
+            ),
+            (
+                "overrides:\n  - files: [a.rb]\n    format: {line_widht: 80}\n",
+                "unknown key `line_widht` in `overrides[0].format`, did you mean `line_width`?",
+                (3, 14),
+            ),
+            (
+                "overrides:\n  - files: [a.rb]\n  - files: [b.rb]\n    format:\n      indent_size: 0\n",
+                "invalid value for `overrides[1].format.indent_size`: must be greater than 0",
+                (5, 20),
+            ),
+            (
+                "- format\n",
+                "invalid type: sequence, expected struct Config",
+                (1, 1),
+            ),
+        ];
+        for (source, message, position) in cases {
+            let err = super::parse_config_source(".rbfmt.yml", source).unwrap_err();
+            match err.downcast_ref::<crate::error::AppError>() {
+                Some(crate::error::AppError::InvalidConfig(diagnostic)) => {
+                    assert_eq!(diagnostic.message, message);
+                    assert_eq!((diagnostic.line, diagnostic.column), position, "{source:?}");
+                }
+                _ => unreachable!("unexpected error: {err}"),
+            }
+        }
+    }
+
+    #[test]
+    fn know_all_keys_and_variants_of_config() -> Result<(), anyhow::Error> {
+        let keys_of = |value: serde_yaml::Value| {
+            let map = value.as_mapping().cloned().unwrap_or_default();
+            map.keys()
+                .map(|key| key.as_str().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        };
+        let mut config_keys = keys_of(serde_yaml::to_value(super::Config::default())?);
+        // The keys not serialized.
+        config_keys.extend(["inherit_from".to_string(), "overrides".to_string()]);
+        assert_eq!(config_keys, super::CONFIG_KEYS);
+        let format_keys = keys_of(serde_yaml::to_value(super::FormatConfig::default())?);
+        assert_eq!(format_keys, super::FORMAT_KEYS);
+
+        for key in ["indent_style", "line_ending", "comment_check"] {
+            let variants = super::variants_of(key);
+            assert!(!variants.is_empty(), "{key}");
+            for variant in variants {
+                let source = match key {
+                    "comment_check" => format!("{key}: {variant}"),
+                    _ => format!("format: {{{key}: {variant}}}"),
+                };
+                serde_yaml::from_str::<super::Config>(&source)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_inherited_and_overridden_config() -> Result<(), anyhow::Error> {
+        let files = TempFiles::new(
+            "config-inheritance",
+            &[
+                (
+                    "shared/base.yml",
+                    "\
+format: {line_width: 80, indent_size: 4}
+verify: true
+overrides:
+  - files: ['app/*.rb']
+    format: {indent_style: tabs}
+",
+                ),
+                (
+                    ".rbfmt.yml",
+                    "\
+inherit_from: shared/base.yml
+format:
+  indent_size: 2
+overrides:
+  - files: ['app/spec/**/*.rb']
+    format: {line_width: 120}
+",
+                ),
+            ],
+        );
+        let root = files.root.clone();
+        let config_path = files.path(".rbfmt.yml");
+
+        let resolved = super::resolve_config_of_path(&root.join("app/a.rb"))?;
+        assert_eq!(resolved.config.format.line_width, 80);
+        assert_eq!(resolved.config.format.indent_size, 2);
+        assert!(resolved.config.verify);
+        assert_eq!(resolved.config.dir, Some(root.clone()));
+        // Patterns in inherited files are relative to the project config file.
+        assert_eq!(
+            resolved.config.format.indent_style,
+            super::IndentStyle::Tabs
+        );
+        assert_eq!(
+            resolved.origins["format.line_width"].to_string(),
+            root.join("shared/base.yml").to_string_lossy()
+        );
+
+        let resolved = super::resolve_config_of_path(&root.join("app/spec/a_spec.rb"))?;
+        assert_eq!(resolved.config.format.line_width, 120);
+        assert_eq!(resolved.config.format.indent_size, 2);
+        assert_eq!(
+            resolved.origins["format.line_width"].to_string(),
+            format!("overrides[0] in {}", config_path.to_string_lossy())
+        );
+
+        std::fs::write(
+            root.join("shared/base.yml"),
+            "inherit_from: ../.rbfmt.yml\n",
+        )?;
+        let err = super::resolve_config_of_dir(&root).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("config files inherit from each other: "));
+        Ok(())
+    }
+
+    #[test]
+    fn apply_editorconfig_under_config_files() -> Result<(), anyhow::Error> {
+        let files = TempFiles::new(
+            "config-editorconfig",
+            &[
+                (
+                    ".editorconfig",
+                    "root = true\n[*.rb]\nmax_line_length = 90\nindent_size = 4\n",
+                ),
+                ("app/.rbfmt.yml", "format: {indent_size: 2}\n"),
+            ],
+        );
+        let root = &files.root;
+        let editorconfig_path = files.path(".editorconfig");
+
+        let resolved = super::resolve_config_of_path(&root.join("app/a.rb"))?;
+        assert_eq!(resolved.config.format.line_width, 90);
+        assert_eq!(resolved.config.format.indent_size, 2);
+        assert_eq!(
+            resolved.origins["format.line_width"].to_string(),
+            format!("[*.rb] in {}", editorconfig_path.to_string_lossy())
+        );
+
+        let resolved = super::resolve_config_of_path(&root.join("app/Gemfile"))?;
+        assert_eq!(resolved.config.format.line_width, 100);
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_dominant_line_ending() {
+        use super::LineEnding::{Auto, Crlf, Lf};
+
+        assert_eq!(Auto.resolve(b"a\r\nb\r\nc\n"), Crlf);
+        assert_eq!(Auto.resolve(b"a\r\nb\nc\n"), Lf);
+        assert_eq!(Auto.resolve(b"a"), Lf);
+        assert_eq!(Lf.resolve(b"a\r\n"), Lf);
+    }
+}
@@ -0,0 +1,300 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use super::{ConfigOrigin, GLOB_MATCH_OPTIONS};
+
+pub(super) const EDITORCONFIG_FILE_NAME: &str = ".editorconfig";
+
+/// A parsed `.editorconfig` file.
+#[derive(Debug, Clone)]
+pub(super) struct EditorConfig {
+    path: PathBuf,
+    dir: PathBuf,
+    root: bool,
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, Clone)]
+struct Section {
+    name: String,
+    patterns: Vec<glob::Pattern>,
+    properties: Vec<(String, String)>,
+}
+
+/// Loads the `.editorconfig` files applying to the files in the absolute directory path,
+/// from the outermost one up to the first file with `root = true`.
+pub(super) fn load_all(dir_path: &Path) -> Result<Vec<EditorConfig>, anyhow::Error> {
+    let mut configs = vec![];
+    for dir in dir_path.ancestors() {
+        let path = dir.join(EDITORCONFIG_FILE_NAME);
+        if !path.is_file() {
+            continue;
+        }
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.to_string_lossy()))?;
+        let config = parse(path, dir.to_path_buf(), &source);
+        let root = config.root;
+        configs.push(config);
+        if root {
+            break;
+        }
+    }
+    configs.reverse();
+    Ok(configs)
+}
+
+fn parse(path: PathBuf, dir: PathBuf, source: &str) -> EditorConfig {
+    let mut config = EditorConfig {
+        path,
+        dir,
+        root: false,
+        sections: vec![],
+    };
+    for line in source.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            config.sections.push(Section {
+                name: name.to_string(),
+                patterns: section_patterns(name),
+                properties: vec![],
+            });
+        } else if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim().to_string();
+            match config.sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                None if key == "root" => config.root = value.eq_ignore_ascii_case("true"),
+                None => {}
+            }
+        }
+    }
+    config
+}
+
+// A section name without slashes matches the file name in any directory,
+// and one with slashes matches the path relative to the directory of the file.
+fn section_patterns(name: &str) -> Vec<glob::Pattern> {
+    let pattern = if name.contains('/') {
+        name.strip_prefix('/').unwrap_or(name).to_string()
+    } else {
+        format!("**/{name}")
+    };
+    // Sections with patterns the glob crate cannot handle never match.
+    expand_braces(&pattern)
+        .iter()
+        .filter_map(|p| glob::Pattern::new(p).ok())
+        .collect()
+}
+
+// Expands alternatives like `{a,b}` into separate patterns, which glob patterns do not support.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(open) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+    let mut depth = 0;
+    let mut close = None;
+    let mut commas = vec![];
+    for (i, ch) in pattern[open..].char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            ',' if depth == 1 => commas.push(open + i),
+            _ => {}
+        }
+    }
+    let Some(close) = close.filter(|_| !commas.is_empty()) else {
+        return vec![pattern.to_string()];
+    };
+    let starts = [open]
+        .into_iter()
+        .chain(commas.iter().copied())
+        .map(|i| i + 1);
+    let ends = commas.iter().copied().chain([close]);
+    starts
+        .zip(ends)
+        .flat_map(|(start, end)| {
+            let alternative = &pattern[start..end];
+            expand_braces(&format!(
+                "{}{alternative}{}",
+                &pattern[..open],
+                &pattern[close + 1..]
+            ))
+        })
+        .collect()
+}
+
+/// Returns the format values for the absolute file path as pairs of a key of `format` config and
+/// its value, with the section each value comes from. Unsupported values are ignored.
+pub(super) fn format_values(
+    configs: &[EditorConfig],
+    file_path: &Path,
+) -> Vec<(&'static str, serde_yaml::Value, ConfigOrigin)> {
+    // Inner files and later sections take precedence.
+    let mut properties: HashMap<&str, (&str, ConfigOrigin)> = HashMap::new();
+    for config in configs {
+        let Ok(relative_path) = file_path.strip_prefix(&config.dir) else {
+            continue;
+        };
+        for section in &config.sections {
+            let matched = section
+                .patterns
+                .iter()
+                .any(|p| p.matches_path_with(relative_path, GLOB_MATCH_OPTIONS));
+            if !matched {
+                continue;
+            }
+            for (key, value) in &section.properties {
+                if value.eq_ignore_ascii_case("unset") {
+                    properties.remove(key.as_str());
+                } else {
+                    let origin = ConfigOrigin::EditorConfig {
+                        path: config.path.clone(),
+                        section: section.name.clone(),
+                    };
+                    properties.insert(key.as_str(), (value.as_str(), origin));
+                }
+            }
+        }
+    }
+
+    let mut values = vec![];
+    for (key, (value, origin)) in &properties {
+        let value = value.to_ascii_lowercase();
+        let format_value = match *key {
+            "max_line_length" => positive_number(&value).map(|n| ("line_width", n)),
+            // `tab` means the same size as `tab_width`.
+            "indent_size" if value == "tab" => properties
+                .get("tab_width")
+                .and_then(|(tab_width, _)| positive_number(tab_width))
+                .map(|n| ("indent_size", n)),
+            "indent_size" => positive_number(&value).map(|n| ("indent_size", n)),
+            "tab_width" => positive_number(&value).map(|n| ("tab_width", n)),
+            "indent_style" => match value.as_str() {
+                "space" => Some(("indent_style", "spaces".into())),
+                "tab" => Some(("indent_style", "tabs".into())),
+                _ => None,
+            },
+            "end_of_line" => match value.as_str() {
+                "lf" | "crlf" => Some(("line_ending", value.into())),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some((key, value)) = format_value {
+            values.push((key, value, origin.clone()));
+        }
+    }
+    values.sort_by_key(|(key, _, _)| *key);
+    values
+}
+
+fn positive_number(value: &str) -> Option<serde_yaml::Value> {
+    value
+        .parse::<usize>()
+        .ok()
+        .filter(|n| *n > 0)
+        .map(serde_yaml::Value::from)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use similar_asserts::assert_eq;
+
+    #[test]
+    fn format_values_of_matching_sections() {
+        let source = "\
+root = true
+
+[*]
+indent_style = space
+indent_size = 4
+end_of_line = crlf
+
+# Ruby files
+[*.{rb,rake}]
+indent_size = 2
+max_line_length = off
+
+[lib/**/*.rb]
+max_line_length = 120
+
+[vendor/**]
+indent_style = tab
+indent_size = tab
+tab_width = 8
+
+[Gemfile]
+end_of_line = unset
+";
+        let dir = PathBuf::from("/project");
+        let config = super::parse(dir.join(".editorconfig"), dir, source);
+        assert!(config.root);
+        let configs = [config];
+
+        let values_of = |path: &str| {
+            super::format_values(&configs, Path::new(path))
+                .into_iter()
+                .map(|(key, value, origin)| {
+                    let value = serde_json::to_string(&value).unwrap();
+                    (key, value, origin.to_string())
+                })
+                .collect::<Vec<_>>()
+        };
+        let origin = |section: &str| format!("[{section}] in /project/.editorconfig");
+
+        assert_eq!(
+            values_of("/project/app/a.rb"),
+            vec![
+                ("indent_size", "2".to_string(), origin("*.{rb,rake}")),
+                ("indent_style", "\"spaces\"".to_string(), origin("*")),
+                ("line_ending", "\"crlf\"".to_string(), origin("*")),
+            ]
+        );
+        assert_eq!(
+            values_of("/project/Gemfile"),
+            vec![
+                ("indent_size", "4".to_string(), origin("*")),
+                ("indent_style", "\"spaces\"".to_string(), origin("*")),
+            ]
+        );
+        assert_eq!(
+            values_of("/project/lib/a/b.rb")[3],
+            ("line_width", "120".to_string(), origin("lib/**/*.rb"))
+        );
+        assert_eq!(
+            values_of("/project/vendor/a.rb"),
+            vec![
+                ("indent_size", "8".to_string(), origin("vendor/**")),
+                ("indent_style", "\"tabs\"".to_string(), origin("vendor/**")),
+                ("line_ending", "\"crlf\"".to_string(), origin("*")),
+                ("tab_width", "8".to_string(), origin("vendor/**")),
+            ]
+        );
+        assert!(values_of("/other/a.rb").is_empty());
+    }
+
+    #[test]
+    fn expand_brace_alternatives() {
+        assert_eq!(
+            super::expand_braces("{a,b/{c,d}}.rb"),
+            vec!["a.rb", "b/c.rb", "b/d.rb"]
+        );
+        assert_eq!(super::expand_braces("{1..3}.rb"), vec!["{1..3}.rb"]);
+    }
+}
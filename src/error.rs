@@ -1,21 +1,351 @@
 #[derive(Debug)]
 pub enum AppError {
-    ParseFailed(Vec<String>),
+    ParseFailed(Vec<Diagnostic>),
+    Unsupported {
+        node_kind: String,
+        location: SourceLocation,
+    },
+    /// The formatted code does not have the same meaning as the source.
+    MeaningChanged(Box<MeaningChange>),
+    /// The formatted code has a syntax error in it.
+    BrokenOutput(Box<Diagnostic>),
+    /// Some comments are lost or duplicated in the formatted code.
+    CommentsChanged(Vec<Diagnostic>),
+    /// Formatting the formatted code again changes it.
+    NotIdempotent {
+        first: String,
+        second: String,
+    },
+    /// A config file has an unknown key or an invalid value.
+    InvalidConfig(Box<Diagnostic>),
     Misc(String),
 }
 
+impl AppError {
+    /// Sets the path of the source file to the diagnostics.
+    pub fn with_path(mut self, path: &str) -> Self {
+        match &mut self {
+            Self::ParseFailed(diagnostics) => {
+                for diagnostic in diagnostics.iter_mut() {
+                    diagnostic.path = Some(path.to_string());
+                }
+            }
+            Self::Unsupported { location, .. } => {
+                location.path = Some(path.to_string());
+            }
+            Self::CommentsChanged(diagnostics) => {
+                // The diagnostics in the formatted code have their own path.
+                for diagnostic in diagnostics.iter_mut().filter(|d| d.path.is_none()) {
+                    diagnostic.path = Some(path.to_string());
+                }
+            }
+            Self::MeaningChanged(change) => {
+                change.original.path = Some(path.to_string());
+            }
+            Self::BrokenOutput(_)
+            | Self::NotIdempotent { .. }
+            | Self::InvalidConfig(_)
+            | Self::Misc(_) => {}
+        }
+        self
+    }
+}
+
 impl std::fmt::Display for AppError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Self::ParseFailed(messages) => {
-                writeln!(f, "parse error:")?;
-                for message in messages.iter() {
-                    writeln!(f, "{message}")?;
+            Self::ParseFailed(diagnostics) => {
+                for (i, diagnostic) in diagnostics.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    writeln!(f, "{diagnostic}")?;
                 }
             }
+            Self::Unsupported {
+                node_kind,
+                location,
+            } => write!(f, "unsupported syntax: {node_kind} at {location}")?,
+            Self::MeaningChanged(change) => {
+                writeln!(
+                    f,
+                    "formatting changed the meaning of the code (formatter bug)"
+                )?;
+                writeln!(f, "first difference at: {}", change.node_path)?;
+                writeln!(f, "{}\n", change.original)?;
+                write!(f, "{}", change.formatted)?;
+            }
+            Self::CommentsChanged(diagnostics) => {
+                writeln!(f, "formatting lost or duplicated comments (formatter bug)")?;
+                let rendered = diagnostics
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "{}", rendered.join("\n\n"))?;
+            }
+            Self::BrokenOutput(diagnostic) => {
+                writeln!(f, "formatted code has a syntax error (formatter bug)")?;
+                write!(f, "{diagnostic}")?;
+            }
+            Self::NotIdempotent { first, second } => {
+                writeln!(f, "formatting the output again changes it (formatter bug)")?;
+                let diff = similar::TextDiff::from_lines(first, second);
+                let diff = diff
+                    .unified_diff()
+                    .header("first pass", "second pass")
+                    .to_string();
+                write!(f, "{}", diff.trim_end())?;
+            }
+            Self::InvalidConfig(diagnostic) => write!(f, "{diagnostic}")?,
             Self::Misc(message) => write!(f, "{message}")?,
         };
         Ok(())
     }
 }
 impl std::error::Error for AppError {}
+
+/// The first difference between the meanings of the source and the formatted code.
+#[derive(Debug)]
+pub struct MeaningChange {
+    /// The path to the node that differs, like `ProgramNode.statements > StatementsNode.body[0]`.
+    pub node_path: String,
+    /// The node in the source.
+    pub original: Diagnostic,
+    /// The node in the formatted code.
+    pub formatted: Diagnostic,
+}
+
+/// A position in a source code by 1-based line and column numbers.
+#[derive(Debug, Clone)]
+pub struct SourceLocation {
+    pub path: Option<String>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourceLocation {
+    pub(crate) fn new(source: &[u8], offset: usize) -> Self {
+        let (line, column) = line_and_column(source, offset);
+        Self {
+            path: None,
+            line,
+            column,
+        }
+    }
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let path = self.path.as_deref().unwrap_or("<source>");
+        write!(f, "{path}:{}:{}", self.line, self.column)
+    }
+}
+
+// Column numbers are counted by characters, not bytes.
+fn line_and_column(source: &[u8], offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let line_start = source[..offset]
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line = source[..offset].iter().filter(|b| **b == b'\n').count() + 1;
+    let column = String::from_utf8_lossy(&source[line_start..offset])
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A problem found in a source code, located by 1-based line and column numbers.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub path: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub severity: Severity,
+    source_line: String,
+    underline_len: usize,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(
+        severity: Severity,
+        source: &[u8],
+        start: usize,
+        end: usize,
+        message: String,
+    ) -> Self {
+        let start = start.min(source.len());
+        let end = end.clamp(start, source.len());
+        let line_start = source[..start]
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(source.len(), |i| start + i);
+        let (line, column) = line_and_column(source, start);
+        let underline_len = String::from_utf8_lossy(&source[start..end.min(line_end)])
+            .chars()
+            .count();
+        let source_line = String::from_utf8_lossy(&source[line_start..line_end])
+            .trim_end_matches('\r')
+            .to_string();
+        Self {
+            path: None,
+            line,
+            column,
+            message,
+            severity,
+            source_line,
+            underline_len: underline_len.max(1),
+        }
+    }
+
+    /// The number of characters of the problematic part in the line.
+    pub(crate) fn width(&self) -> usize {
+        self.underline_len
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    // Render the diagnostic like a compiler does:
+    //
+    //   error: unexpected end-of-input
+    //    --> foo.rb:1:5
+    //     |
+    //   1 | foo(
+    //     |     ^
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let path = self.path.as_deref().unwrap_or("<source>");
+        let line_number = self.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        // Keep tabs in the underline so that the caret is placed at the same column.
+        let underline_prefix = self
+            .source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        writeln!(f, "{}: {}", self.severity, self.message)?;
+        writeln!(f, "{gutter}--> {path}:{}:{}", self.line, self.column)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line_number} | {}", self.source_line)?;
+        write!(
+            f,
+            "{gutter} | {underline_prefix}{}",
+            "^".repeat(self.underline_len)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use similar_asserts::assert_eq;
+
+    use super::{Diagnostic, Severity};
+
+    #[test]
+    fn render_diagnostic_with_source_excerpt() {
+        let source = b"a = 1\n\tfoo(bar, \xe3\x81\x82 baz\nb = 2\n";
+        let start = source.iter().position(|b| *b == b'b').unwrap();
+        let mut diagnostic = Diagnostic::new(
+            Severity::Error,
+            source,
+            start + 9,
+            start + 12,
+            "unexpected identifier".to_string(),
+        );
+        diagnostic.path = Some("a.rb".to_string());
+
+        assert_eq!((diagnostic.line, diagnostic.column), (2, 13));
+        let want = "\
+error: unexpected identifier
+ --> a.rb:2:13
+  |
+2 | \tfoo(bar, \u{3042} baz
+  | \t           ^^^";
+        assert_eq!(diagnostic.to_string(), want);
+    }
+
+    #[test]
+    fn render_non_idempotent_output_as_diff() {
+        let err = super::AppError::NotIdempotent {
+            first: "a = 1\nfoo(1, 2)\nb = 2\n".to_string(),
+            second: "a = 1\nfoo(\n  1,\n  2\n)\nb = 2\n".to_string(),
+        };
+        let want = "\
+formatting the output again changes it (formatter bug)
+--- first pass
++++ second pass
+@@ -1,3 +1,6 @@
+ a = 1
+-foo(1, 2)
++foo(
++  1,
++  2
++)
+ b = 2";
+        assert_eq!(err.to_string(), want);
+    }
+
+    #[test]
+    fn render_meaning_change_with_both_spans() {
+        let note = |source: &[u8], start: usize, end: usize, message: &str| {
+            Diagnostic::new(Severity::Note, source, start, end, message.to_string())
+        };
+        let mut formatted = note(
+            b"a = 1\nfoo(bar.baz)\n",
+            10,
+            17,
+            "formatted: [CallNode] baz",
+        );
+        formatted.path = Some("<formatted>".to_string());
+        let err = super::AppError::MeaningChanged(Box::new(super::MeaningChange {
+            node_path: "ProgramNode.statements > StatementsNode.body[1] > CallNode.arguments"
+                .to_string(),
+            original: note(
+                b"a = 1\nfoo bar . qux\n",
+                10,
+                19,
+                "original: [CallNode] qux",
+            ),
+            formatted,
+        }))
+        .with_path("a.rb");
+        let want = "\
+formatting changed the meaning of the code (formatter bug)
+first difference at: ProgramNode.statements > StatementsNode.body[1] > CallNode.arguments
+note: original: [CallNode] qux
+ --> a.rb:2:5
+  |
+2 | foo bar . qux
+  |     ^^^^^^^^^
+
+note: formatted: [CallNode] baz
+ --> <formatted>:2:5
+  |
+2 | foo(bar.baz)
+  |     ^^^^^^^";
+        assert_eq!(err.to_string(), want);
+    }
+}
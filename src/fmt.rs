@@ -1,34 +1,102 @@
 mod node;
 mod output;
+mod range;
 mod shape;
 mod trivia;
 
+use std::ops::Range;
+
 pub(crate) use node::*;
 pub(crate) use output::HeredocMap;
 pub(crate) use trivia::{Comment, LeadingTrivia, LineTrivia, TrailingTrivia};
 
-use self::output::{FormatContext, Output};
+use self::{
+    output::{FormatContext, Output},
+    range::RangeTarget,
+};
+use crate::config::{IndentStyle, LineEnding};
 
 pub(crate) fn format(
     config: crate::config::FormatConfig,
     node: Node,
     heredoc_map: HeredocMap,
-) -> String {
-    let config = FormatConfig {
-        line_width: config.line_width,
-        indent_size: 2,
+    data_section: Option<Vec<u8>>,
+) -> Vec<u8> {
+    let ctx = FormatContext {
+        heredoc_map,
+        range_target: None,
     };
-    let ctx = FormatContext { heredoc_map };
-    let mut output = Output::new(config);
+    let mut output = Output::new(FormatConfig::new(config));
     node.format(&mut output, &ctx);
     if !output.buffer.is_empty() {
         output.break_line(&ctx);
     }
-    output.buffer
+    let mut code = output.buffer.into_bytes();
+    if let Some(data_section) = data_section {
+        code.extend(data_section);
+    }
+    code
 }
 
-#[derive(Debug)]
+/// Formats only the statements on the lines in the given byte range of the source.
+/// The range grows to the smallest set of whole statements covering it, and the bytes outside
+/// of them are kept as they are. Returns the code and the range of the formatted part in it.
+pub(crate) fn format_range(
+    config: crate::config::FormatConfig,
+    node: Node,
+    heredoc_map: HeredocMap,
+    source: &[u8],
+    lines: Range<usize>,
+) -> (Vec<u8>, Range<usize>) {
+    let ctx = FormatContext {
+        heredoc_map,
+        range_target: Some(RangeTarget::new(source.to_vec(), lines.clone())),
+    };
+    let mut output = Output::new(FormatConfig::new(config));
+    match &node.kind {
+        // Go through the statements of the program as a block so that they are always chosen from.
+        Kind::Statements(statements) => statements.format(&mut output, &ctx, true),
+        _ => node.format(&mut output, &ctx),
+    }
+    match output.range_selection {
+        Some(selection) => {
+            let range = selection.source_range;
+            let mut code = source[..range.start].to_vec();
+            code.extend(selection.code.as_bytes());
+            let formatted = range.start..code.len();
+            code.extend(&source[range.end..]);
+            (code, formatted)
+        }
+        None => (source.to_vec(), lines),
+    }
+}
+
+#[derive(Debug, Clone)]
 pub(crate) struct FormatConfig {
     line_width: usize,
     indent_size: usize,
+    indent_style: IndentStyle,
+    tab_width: usize,
+    line_break: &'static str,
+}
+
+impl FormatConfig {
+    fn new(config: crate::config::FormatConfig) -> Self {
+        let tab_width = config.tab_width.unwrap_or(config.indent_size).max(1);
+        let indent_size = match config.indent_style {
+            IndentStyle::Spaces => config.indent_size,
+            IndentStyle::Tabs => tab_width,
+        };
+        Self {
+            line_width: config.line_width,
+            indent_size,
+            indent_style: config.indent_style,
+            tab_width,
+            // `Auto` is resolved from the source before formatting.
+            line_break: match config.line_ending {
+                LineEnding::Crlf => "\r\n",
+                LineEnding::Lf | LineEnding::Auto => "\n",
+            },
+        }
+    }
 }
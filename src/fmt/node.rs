@@ -34,6 +34,7 @@ mod singleton_class;
 mod statements;
 mod string_like;
 mod ternary;
+mod verbatim;
 mod virtual_end;
 mod whiles;
 
@@ -45,7 +46,7 @@ pub(crate) use self::{
     constant_path::*, def::*, dyn_string_like::*, fors::*, hash::*, hash_pattern::*, heredoc::*,
     ifs::*, infix_chain::*, lambda::*, match_assign::*, method_chain::*, multi_assign_target::*,
     parens::*, postmodifier::*, pre_post_exec::*, prefix::*, range_like::*, singleton_class::*,
-    statements::*, string_like::*, ternary::*, virtual_end::*, whiles::*,
+    statements::*, string_like::*, ternary::*, verbatim::*, virtual_end::*, whiles::*,
 };
 
 use super::{
@@ -146,6 +147,7 @@ pub(crate) enum Kind {
     AltPatternChain(AltPatternChain),
     PrePostExec(PrePostExec),
     Alias(Alias),
+    Verbatim(Verbatim),
 }
 
 impl Kind {
@@ -186,6 +188,7 @@ impl Kind {
             Kind::AltPatternChain(chain) => chain.format(o, ctx),
             Kind::PrePostExec(exec) => exec.format(o, ctx),
             Kind::Alias(alias) => alias.format(o, ctx),
+            Kind::Verbatim(verbatim) => verbatim.format(o, ctx),
         }
     }
 
@@ -226,6 +229,7 @@ impl Kind {
             Self::AltPatternChain(chain) => chain.shape(),
             Self::PrePostExec(exec) => exec.shape,
             Self::Alias(alias) => alias.shape,
+            Self::Verbatim(verbatim) => verbatim.shape,
         }
     }
 
@@ -251,6 +255,7 @@ impl Kind {
             },
             Self::StringLike(str) => str.shape.concat_style(),
             Self::HeredocOpening(opening) => opening.shape.concat_style(),
+            Self::Verbatim(verbatim) => verbatim.shape.concat_style(),
             Self::Parens(_) => ConcatStyle::Horizontal {
                 min_first_line_len: "(".len(),
             },
@@ -1,5 +1,8 @@
+use std::ops::Range;
+
 use crate::fmt::{
     output::{FormatContext, Output},
+    range::RangeChoice,
     shape::Shape,
     trivia::EmptyLineHandling,
 };
@@ -10,6 +13,8 @@ use super::{Kind, Node, VirtualEnd};
 pub(crate) struct Statements {
     pub shape: Shape,
     pub nodes: Vec<Node>,
+    /// The source byte ranges of the nodes, including their leading comments, if known.
+    pub spans: Vec<Option<Range<usize>>>,
     pub virtual_end: Option<VirtualEnd>,
 }
 
@@ -18,6 +23,7 @@ impl Statements {
         Self {
             shape: Shape::inline(0),
             nodes: vec![],
+            spans: vec![],
             virtual_end: None,
         }
     }
@@ -29,6 +35,14 @@ impl Statements {
             self.shape = Shape::Multilines;
         }
         self.nodes.push(node);
+        self.spans.push(None);
+    }
+
+    pub(crate) fn append_node_at(&mut self, node: Node, span: Range<usize>) {
+        self.append_node(node);
+        if let Some(last) = self.spans.last_mut() {
+            *last = Some(span);
+        }
     }
 
     pub(crate) fn set_virtual_end(&mut self, end: Option<VirtualEnd>) {
@@ -53,21 +67,18 @@ impl Statements {
             }
             return;
         }
+        let inside = self.select_range(o, ctx);
         for (i, n) in self.nodes.iter().enumerate() {
             if i > 0 {
                 o.break_line(ctx);
             }
-            n.leading_trivia.format(
-                o,
-                ctx,
-                EmptyLineHandling::Trim {
-                    start: i == 0,
-                    end: false,
-                },
-            );
-            o.put_indent_if_needed();
-            n.format(o, ctx);
-            n.trailing_trivia.format(o);
+            Self::format_node(o, ctx, n, i == 0);
+            if inside == Some(i) && o.range_selection.is_none() {
+                // No statements inside the node are chosen so choose the node itself.
+                if let Some(target) = &ctx.range_target {
+                    o.range_selection = target.select(o, ctx, self, i..i + 1);
+                }
+            }
         }
         o.write_trivia_at_virtual_end(
             ctx,
@@ -76,4 +87,35 @@ impl Statements {
             self.nodes.is_empty(),
         );
     }
+
+    pub(crate) fn format_node(o: &mut Output, ctx: &FormatContext, node: &Node, is_first: bool) {
+        node.leading_trivia.format(
+            o,
+            ctx,
+            EmptyLineHandling::Trim {
+                start: is_first,
+                end: false,
+            },
+        );
+        o.put_indent_if_needed();
+        node.format(o, ctx);
+        node.trailing_trivia.format(o);
+    }
+
+    // Chooses the statements to format when formatting only some lines.
+    // Returns the index of the node if the lines are inside of it.
+    fn select_range(&self, o: &mut Output, ctx: &FormatContext) -> Option<usize> {
+        let target = ctx.range_target.as_ref()?;
+        if o.range_selection.is_some() {
+            return None;
+        }
+        match target.choose(self) {
+            RangeChoice::Nothing => None,
+            RangeChoice::Statements(indices) => {
+                o.range_selection = target.select(o, ctx, self, indices);
+                None
+            }
+            RangeChoice::Inside(index) => Some(index),
+        }
+    }
 }
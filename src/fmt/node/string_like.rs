@@ -10,6 +10,8 @@ pub(crate) struct StringLike {
 
 impl StringLike {
     pub(crate) fn new(opening: Option<String>, value: Vec<u8>, closing: Option<String>) -> Self {
+        // The output writes its own line breaks.
+        let value = normalize_line_breaks(value);
         let opening_shape = opening.as_ref().map_or(Shape::inline(0), |s| {
             if s.chars().any(|c| c == '\n') {
                 Shape::Multilines
@@ -50,3 +52,18 @@ impl StringLike {
         }
     }
 }
+
+// Replaces CRLF with LF so that the lines of CRLF sources are handled in the same way.
+fn normalize_line_breaks(value: Vec<u8>) -> Vec<u8> {
+    if !value.windows(2).any(|w| w == b"\r\n") {
+        return value;
+    }
+    let mut normalized = Vec::with_capacity(value.len());
+    for (i, b) in value.iter().enumerate() {
+        if *b == b'\r' && value.get(i + 1) == Some(&b'\n') {
+            continue;
+        }
+        normalized.push(*b);
+    }
+    normalized
+}
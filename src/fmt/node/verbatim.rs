@@ -0,0 +1,94 @@
+use std::ops::Range;
+
+use crate::fmt::{
+    output::{FormatContext, Output},
+    shape::Shape,
+};
+
+/// A node we cannot format, written as it is in the original source.
+/// The lines after the first one keep their indentation relative to
+/// the line where the node starts.
+#[derive(Debug)]
+pub(crate) struct Verbatim {
+    pub shape: Shape,
+    pub value: String,
+    /// The leading spaces and tabs of the line where the node starts.
+    pub base_indent: String,
+    /// The indices of the lines starting inside string-like literals or embedded documents,
+    /// whose leading spaces are a part of the program so they are never shifted.
+    pub fixed_lines: Vec<usize>,
+}
+
+impl Verbatim {
+    /// Creates a verbatim node from its source. The literal ranges are the byte ranges
+    /// relative to the source where lines must be kept as they are.
+    pub(crate) fn new(source: &[u8], base_indent: String, literal_ranges: &[Range<usize>]) -> Self {
+        let fixed_lines = source
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'\n')
+            .map(|(i, _)| i + 1)
+            .enumerate()
+            .filter(|(_, line_start)| {
+                literal_ranges
+                    .iter()
+                    .any(|r| r.start < *line_start && *line_start < r.end)
+            })
+            .map(|(i, _)| i + 1)
+            .collect();
+        // The output writes its own line breaks.
+        let value = String::from_utf8_lossy(source).replace("\r\n", "\n");
+        let shape = if value.contains('\n') {
+            Shape::Multilines
+        } else {
+            Shape::inline(value.len())
+        };
+        Self {
+            shape,
+            value,
+            base_indent,
+            fixed_lines,
+        }
+    }
+
+    pub(crate) fn format(&self, o: &mut Output, ctx: &FormatContext) {
+        let tab_width = o.config.tab_width;
+        let base_width = indent_width(&self.base_indent, tab_width);
+        for (i, line) in self.value.split('\n').enumerate() {
+            if i == 0 {
+                o.push_str(line);
+                continue;
+            }
+            o.break_line(ctx);
+            if line.is_empty() {
+                continue;
+            }
+            let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+            if indent_width(&line[..indent], tab_width) >= base_width
+                && !self.fixed_lines.contains(&i)
+            {
+                // Strip the base indentation by width. A tab crossing it leaves the excess as spaces.
+                let mut width = 0;
+                let mut stripped = 0;
+                while width < base_width {
+                    stripped += 1;
+                    width = indent_width(&line[..stripped], tab_width);
+                }
+                o.put_indent();
+                o.push_str(&" ".repeat(width - base_width));
+                o.push_str(&line[stripped..]);
+            } else {
+                // The line is less indented than the start of the node
+                // or is a part of a literal, so we cannot shift it.
+                o.push_str(line);
+            }
+        }
+    }
+}
+
+fn indent_width(indent: &str, tab_width: usize) -> usize {
+    indent
+        .bytes()
+        .map(|b| if b == b'\t' { tab_width } else { 1 })
+        .sum()
+}
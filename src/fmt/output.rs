@@ -1,7 +1,9 @@
 use super::{
     node::{Heredoc, Pos, VirtualEnd},
+    range::{RangeSelection, RangeTarget},
     FormatConfig, HeredocState,
 };
+use crate::config::IndentStyle;
 use std::{
     collections::{HashMap, VecDeque},
     mem,
@@ -12,6 +14,8 @@ pub(crate) type HeredocMap = HashMap<Pos, Heredoc>;
 #[derive(Debug)]
 pub(crate) struct FormatContext {
     pub heredoc_map: HeredocMap,
+    /// The lines to format, if formatting only a part of the source.
+    pub range_target: Option<RangeTarget>,
 }
 
 #[derive(Debug)]
@@ -43,6 +47,8 @@ pub(crate) struct Output {
     pub buffer: String,
     pub indent: usize,
     pub heredoc_queue: VecDeque<HeredocState>,
+    /// The statements chosen for the range target, formatted separately from the buffer.
+    pub range_selection: Option<RangeSelection>,
     drafts: Vec<Draft>,
 }
 
@@ -55,6 +61,7 @@ impl Output {
             buffer: String::new(),
             indent: 0,
             heredoc_queue: VecDeque::new(),
+            range_selection: None,
             drafts: vec![],
         }
     }
@@ -110,7 +117,15 @@ impl Output {
     pub(super) fn push_str(&mut self, str: &str) {
         // XXX: When we push the content of a string, it could contain a line-break.
         // In that case, `remaining_width` could be wrong.
-        self.buffer.push_str(str);
+        if str.contains('\n') {
+            // Write the line breaks in strings and heredocs in the same way as the others.
+            // Ruby reads CRLF as LF even in them so this does not change their values.
+            let normalized = str.replace("\r\n", "\n");
+            self.buffer
+                .push_str(&normalized.replace('\n', self.config.line_break));
+        } else {
+            self.buffer.push_str(str);
+        }
         self.remaining_width = self.remaining_width.saturating_sub(str.len());
     }
 
@@ -121,9 +136,16 @@ impl Output {
     }
 
     pub(super) fn put_indent(&mut self) {
-        let spaces = " ".repeat(self.indent);
-        self.buffer.push_str(&spaces);
-        self.remaining_width = self.remaining_width.saturating_sub(spaces.len());
+        match self.config.indent_style {
+            IndentStyle::Spaces => self.buffer.push_str(&" ".repeat(self.indent)),
+            IndentStyle::Tabs => {
+                // The indentation of partially formatted lines may not be a multiple of tabs.
+                let tab_width = self.config.tab_width;
+                self.buffer.push_str(&"\t".repeat(self.indent / tab_width));
+                self.buffer.push_str(&" ".repeat(self.indent % tab_width));
+            }
+        }
+        self.remaining_width = self.remaining_width.saturating_sub(self.indent);
     }
 
     pub(super) fn indent(&mut self) {
@@ -143,7 +165,7 @@ impl Output {
     }
 
     fn break_line_without_popping_heredoc(&mut self) {
-        self.buffer.push('\n');
+        self.buffer.push_str(self.config.line_break);
         self.remaining_width = self.config.line_width;
         self.line_count += 1;
     }
@@ -0,0 +1,163 @@
+use std::{cell::Cell, ops::Range};
+
+use super::{
+    node::Statements,
+    output::{FormatContext, Output},
+};
+
+/// The lines to format when formatting only a part of the source.
+#[derive(Debug)]
+pub(crate) struct RangeTarget {
+    pub source: Vec<u8>,
+    /// The byte range of the lines, from the start of the first line to the end of the last one.
+    pub lines: Range<usize>,
+    // Whether we are formatting the chosen statements, where no more statements are chosen.
+    selecting: Cell<bool>,
+}
+
+/// The statements chosen to format and their formatted code.
+#[derive(Debug)]
+pub(crate) struct RangeSelection {
+    /// The byte range of the lines the statements are written on in the source.
+    pub source_range: Range<usize>,
+    pub code: String,
+}
+
+#[derive(Debug)]
+pub(crate) enum RangeChoice {
+    /// No statements are on the target lines.
+    Nothing,
+    /// The statements at the indices cover the target lines.
+    Statements(Range<usize>),
+    /// The target lines are inside the statement at the index, so a statement in it may be
+    /// a smaller choice.
+    Inside(usize),
+}
+
+impl RangeTarget {
+    pub(crate) fn new(source: Vec<u8>, lines: Range<usize>) -> Self {
+        Self {
+            source,
+            lines,
+            selecting: Cell::new(false),
+        }
+    }
+
+    /// Chooses the statements on the target lines, including the ones sharing a line with them.
+    pub(crate) fn choose(&self, statements: &Statements) -> RangeChoice {
+        if self.selecting.get() {
+            return RangeChoice::Nothing;
+        }
+        let spans = &statements.spans;
+        let overlaps = |span: &Option<Range<usize>>| {
+            span.as_ref().is_some_and(|span| {
+                self.line_start(span.start) < self.lines.end
+                    && self.lines.start < self.line_end(span.end)
+            })
+        };
+        let (Some(mut first), Some(mut last)) = (
+            spans.iter().position(overlaps),
+            spans.iter().rposition(overlaps),
+        ) else {
+            return RangeChoice::Nothing;
+        };
+        let Some(run) = spans[first..=last]
+            .iter()
+            .map(Option::as_ref)
+            .collect::<Option<Vec<_>>>()
+        else {
+            // Some statements have no location so we cannot replace them.
+            return RangeChoice::Nothing;
+        };
+        if let [span] = run[..] {
+            let first_line_end = self.line_end(span.start + 1);
+            let last_line_start = self.line_start(span.end - 1);
+            if first_line_end <= self.lines.start && self.lines.end <= last_line_start {
+                return RangeChoice::Inside(first);
+            }
+        }
+        while first > 0 && self.shares_line(&spans[first - 1], &spans[first]) {
+            first -= 1;
+        }
+        while last + 1 < spans.len() && self.shares_line(&spans[last], &spans[last + 1]) {
+            last += 1;
+        }
+        RangeChoice::Statements(first..last + 1)
+    }
+
+    /// Formats the statements at the indices at the indentation of the line they start.
+    /// Returns None if they share a line with code outside of them.
+    pub(crate) fn select(
+        &self,
+        o: &Output,
+        ctx: &FormatContext,
+        statements: &Statements,
+        indices: Range<usize>,
+    ) -> Option<RangeSelection> {
+        let spans = &statements.spans[indices.clone()];
+        let start = spans.first()?.as_ref()?.start;
+        let end = spans.last()?.as_ref()?.end;
+        let source_range = self.line_start(start)..self.line_end(end);
+        if !self.is_blank(source_range.start..start) || !self.is_line_tail(end) {
+            return None;
+        }
+
+        let tab_width = o.config.tab_width;
+        let indent = self.source[source_range.start..start]
+            .iter()
+            .map(|b| if *b == b'\t' { tab_width } else { 1 })
+            .sum();
+        let mut output = Output::new(o.config.clone());
+        output.indent = indent;
+        self.selecting.set(true);
+        for (i, node) in statements.nodes[indices].iter().enumerate() {
+            if i > 0 {
+                output.break_line(ctx);
+            }
+            Statements::format_node(&mut output, ctx, node, i == 0);
+        }
+        output.break_line(ctx);
+        self.selecting.set(false);
+        let mut code = output.buffer;
+        if !self.source[..source_range.end].ends_with(b"\n") {
+            // Keep the last line without a line break at the end of the source.
+            code.truncate(code.len() - o.config.line_break.len());
+        }
+        Some(RangeSelection { source_range, code })
+    }
+
+    fn shares_line(&self, prev: &Option<Range<usize>>, next: &Option<Range<usize>>) -> bool {
+        match (prev, next) {
+            (Some(prev), Some(next)) => self.line_start(next.start) < self.line_end(prev.end),
+            _ => false,
+        }
+    }
+
+    // The start offset of the line containing the offset.
+    fn line_start(&self, offset: usize) -> usize {
+        self.source[..offset]
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1)
+    }
+
+    // The end offset (after the line break) of the line containing the byte before the offset.
+    fn line_end(&self, offset: usize) -> usize {
+        let last = offset.saturating_sub(1);
+        self.source[last..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(self.source.len(), |i| last + i + 1)
+    }
+
+    fn is_blank(&self, range: Range<usize>) -> bool {
+        self.source[range].iter().all(|b| *b == b' ' || *b == b'\t')
+    }
+
+    // Checks if only a semicolon or a comment follows the offset in the line.
+    fn is_line_tail(&self, offset: usize) -> bool {
+        let rest = String::from_utf8_lossy(&self.source[offset..self.line_end(offset)]);
+        let rest = rest.trim_start().trim_start_matches(';').trim();
+        rest.is_empty() || rest.starts_with('#')
+    }
+}
@@ -1,7 +1,10 @@
-use config::FormatConfig;
-use error::AppError;
+use std::ops::{Range, RangeInclusive};
+
+use config::{CommentCheck, FormatConfig};
+use error::{AppError, Diagnostic, MeaningChange, Severity};
 
 mod cli;
+mod comments;
 mod config;
 mod error;
 mod fmt;
@@ -11,53 +14,264 @@ mod parse;
 #[cfg(test)]
 mod test;
 
-pub fn run() -> Result<(), anyhow::Error> {
+pub use cli::{ExitStatus, INTERNAL_ERROR_EXIT_CODE};
+
+pub fn run() -> Result<ExitStatus, anyhow::Error> {
     cli::run(
         &mut std::io::stdin(),
         &mut std::io::stdout(),
+        &mut std::io::stderr(),
         std::env::args().skip(1),
     )
 }
 
 pub struct FormatResult {
-    code: String,
-    meaning_diff: Option<(String, String)>,
+    code: Vec<u8>,
+    warnings: Vec<Diagnostic>,
+    verbatim_nodes: Vec<Diagnostic>,
+    // Problems found in the formatted code that are not severe enough to fail.
+    check_warnings: Vec<Diagnostic>,
 }
 
-pub fn format_source(source: Vec<u8>, config: FormatConfig) -> Result<FormatResult, AppError> {
+/// Options to check the result of formatting.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FormatOptions {
+    /// Fail if the formatted code does not have the same meaning as the source.
+    pub verify: bool,
+    /// Fail if formatting the formatted code again changes it.
+    pub verify_idempotent: bool,
+    /// What to do when formatting loses or duplicates comments.
+    pub comment_check: CommentCheck,
+}
+
+pub fn format_source(
+    source: Vec<u8>,
+    config: FormatConfig,
+    options: FormatOptions,
+) -> Result<FormatResult, AppError> {
+    format(source, config, options, None)
+}
+
+/// Formats only the statements on the given 1-based line ranges and keeps the rest of the source as it is.
+/// Each range grows to the smallest set of whole statements covering it,
+/// which are formatted at their current indentation.
+/// Lines without any statement on them, like blank lines, are kept as they are.
+/// Comments belong to the statement they lead, except ones after the last statement in a body.
+pub fn format_lines(
+    source: Vec<u8>,
+    config: FormatConfig,
+    options: FormatOptions,
+    lines: &[RangeInclusive<usize>],
+) -> Result<FormatResult, AppError> {
+    // Merge overlapping or adjacent ranges since formatting one of them moves the lines of the other.
+    let mut sorted = lines.to_vec();
+    sorted.sort_by_key(|lines| *lines.start());
+    let mut lines: Vec<RangeInclusive<usize>> = vec![];
+    for range in sorted {
+        match lines.last_mut() {
+            Some(last) if *range.start() <= last.end() + 1 => {
+                *last = *last.start()..=*last.end().max(range.end());
+            }
+            _ => lines.push(range),
+        }
+    }
+    // Format from the bottom so that formatting a range does not move the lines of the others.
+    lines.reverse();
+    let Some((last, rest)) = lines.split_first() else {
+        // Nothing is on the empty range, so this just checks the source.
+        return format(source, config, options, Some(0..0));
+    };
+    let target = byte_range_of_lines(&source, last.clone())?;
+    let mut result = format(source, config.clone(), options, Some(target))?;
+    for lines in rest {
+        let target = byte_range_of_lines(&result.code, lines.clone())?;
+        let next = format(result.code, config.clone(), options, Some(target))?;
+        result.code = next.code;
+        result.check_warnings.extend(next.check_warnings);
+    }
+    Ok(result)
+}
+
+fn format(
+    source: Vec<u8>,
+    config: FormatConfig,
+    options: FormatOptions,
+    target: Option<Range<usize>>,
+) -> Result<FormatResult, AppError> {
+    let config = FormatConfig {
+        line_ending: config.line_ending.resolve(&source),
+        ..config
+    };
     let prism_result = prism::parse(&source);
+    let warnings = parse::warnings_of(&prism_result);
+    let meaning_before = options
+        .verify
+        .then(|| meaning::extract(&prism_result.node()));
+    let comments_before = comments::extract(&prism_result);
 
-    if cfg!(feature = "safety") {
-        let meaning_before = meaning::extract(&prism_result.node());
-        let code = parse_and_format(config, prism_result)?;
-        let meaning_after = meaning::extract(&prism::parse(code.as_bytes()).node());
-        let meaning_diff = if meaning_before == meaning_after {
-            None
-        } else {
-            Some((meaning_before, meaning_after))
-        };
-        Ok(FormatResult { code, meaning_diff })
-    } else {
-        let code = parse_and_format(config, prism_result)?;
-        Ok(FormatResult {
-            code,
-            meaning_diff: None,
-        })
+    let FormattedCode {
+        code,
+        verbatim_nodes,
+        range: formatted_range,
+    } = parse_and_format(config.clone(), prism_result, target)?;
+
+    let check_warnings = check_output(
+        &source,
+        &code,
+        formatted_range,
+        config,
+        options,
+        meaning_before,
+        comments_before,
+    )?;
+    Ok(FormatResult {
+        code,
+        warnings,
+        verbatim_nodes,
+        check_warnings,
+    })
+}
+
+// Check the formatted code according to the options.
+// Returns the problems reported as warnings instead of errors.
+fn check_output(
+    source: &[u8],
+    code: &[u8],
+    formatted_range: Option<Range<usize>>,
+    config: FormatConfig,
+    options: FormatOptions,
+    meaning_before: Option<meaning::Tree>,
+    comments_before: Vec<comments::Comment>,
+) -> Result<Vec<Diagnostic>, AppError> {
+    let output_result = parse_output(code)?;
+
+    let comments_after = comments::extract(&output_result);
+    let severity = match options.comment_check {
+        CommentCheck::Error => Severity::Error,
+        CommentCheck::Warning => Severity::Warning,
+    };
+    let comment_diagnostics =
+        comments::compare(source, &comments_before, code, &comments_after, severity);
+    if options.comment_check == CommentCheck::Error && !comment_diagnostics.is_empty() {
+        return Err(AppError::CommentsChanged(comment_diagnostics));
+    }
+
+    if let Some(meaning_before) = meaning_before {
+        let meaning_after = meaning::extract(&output_result.node());
+        if let Some(divergence) = meaning::first_divergence(&meaning_before, &meaning_after) {
+            return Err(meaning_change_error(source, code, divergence));
+        }
+    }
+    if options.verify_idempotent {
+        let code_again = parse_and_format(config, output_result, formatted_range)
+            .map_err(|err| AppError::Misc(format!("failed to format the output again: {err}")))?
+            .code;
+        if code_again != code {
+            return Err(AppError::NotIdempotent {
+                first: String::from_utf8_lossy(code).to_string(),
+                second: String::from_utf8_lossy(&code_again).to_string(),
+            });
+        }
     }
+    Ok(comment_diagnostics)
+}
+
+// Parses the formatted code to never return broken code even if the formatter has a bug.
+fn parse_output(code: &[u8]) -> Result<prism::ParseResult<'_>, AppError> {
+    let output_result = prism::parse(code);
+    if let Some(error) = output_result.errors().next() {
+        let mut diagnostic = parse::diagnostic_of(&output_result, Severity::Error, error);
+        diagnostic.path = Some("<formatted>".to_string());
+        return Err(AppError::BrokenOutput(Box::new(diagnostic)));
+    }
+    Ok(output_result)
+}
+
+fn meaning_change_error(source: &[u8], code: &[u8], divergence: meaning::Divergence) -> AppError {
+    let diagnostic = |code: &[u8], item: meaning::DivergentItem, label: &str| {
+        let message = format!("{label}: {}", item.summary);
+        Diagnostic::new(
+            Severity::Note,
+            code,
+            item.span.start,
+            item.span.end,
+            message,
+        )
+    };
+    let mut formatted = diagnostic(code, divergence.after, "formatted");
+    formatted.path = Some("<formatted>".to_string());
+    AppError::MeaningChanged(Box::new(MeaningChange {
+        node_path: divergence.path,
+        original: diagnostic(source, divergence.before, "original"),
+        formatted,
+    }))
+}
+
+struct FormattedCode {
+    code: Vec<u8>,
+    /// The nodes written as they are.
+    verbatim_nodes: Vec<Diagnostic>,
+    /// The range of the formatted part in the code if only the target lines are formatted.
+    range: Option<Range<usize>>,
 }
 
 fn parse_and_format(
     config: FormatConfig,
     prism_result: prism::ParseResult,
-) -> Result<String, AppError> {
+    target: Option<Range<usize>>,
+) -> Result<FormattedCode, AppError> {
+    let source = target.as_ref().map(|_| prism_result.source().to_vec());
     let result = parse::parse_from_prism_result(prism_result)?;
-    let formatted = fmt::format(config, result.node, result.heredoc_map);
-    Ok(formatted)
+    match (source, target) {
+        (Some(source), Some(lines)) => {
+            let (formatted, range) =
+                fmt::format_range(config, result.node, result.heredoc_map, &source, lines);
+            Ok(FormattedCode {
+                code: formatted,
+                verbatim_nodes: result.verbatim_nodes,
+                range: Some(range),
+            })
+        }
+        _ => {
+            let formatted =
+                fmt::format(config, result.node, result.heredoc_map, result.data_section);
+            Ok(FormattedCode {
+                code: formatted,
+                verbatim_nodes: result.verbatim_nodes,
+                range: None,
+            })
+        }
+    }
+}
+
+// Converts 1-based lines to the byte range from the start of the first line to the end of the last one.
+fn byte_range_of_lines(
+    source: &[u8],
+    lines: RangeInclusive<usize>,
+) -> Result<Range<usize>, AppError> {
+    let (first, last) = lines.into_inner();
+    if first == 0 || first > last {
+        return Err(AppError::Misc(format!(
+            "invalid line range: {first}:{last}"
+        )));
+    }
+    let line_starts = std::iter::once(0)
+        .chain(
+            source
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| **b == b'\n')
+                .map(|(i, _)| i + 1),
+        )
+        .collect::<Vec<_>>();
+    let start = line_starts.get(first - 1).copied().unwrap_or(source.len());
+    let end = line_starts.get(last).copied().unwrap_or(source.len());
+    Ok(start..end)
 }
 
 pub fn extract_meaning(target_path: &String) -> Result<String, anyhow::Error> {
     let source = std::fs::read_to_string(target_path)?;
     let prism_result = prism::parse(source.as_bytes());
     let meaning = meaning::extract(&prism_result.node());
-    Ok(meaning)
+    Ok(meaning.to_string())
 }
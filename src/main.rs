@@ -1,14 +1,16 @@
 use std::process::ExitCode;
 
+use rbfmt::INTERNAL_ERROR_EXIT_CODE;
+
 fn main() -> ExitCode {
     env_logger::init();
 
     let result = rbfmt::run();
     match result {
-        Ok(_) => ExitCode::SUCCESS,
+        Ok(status) => ExitCode::from(status.exit_code()),
         Err(err) => {
             eprintln!("{:?}", err);
-            ExitCode::FAILURE
+            ExitCode::from(INTERNAL_ERROR_EXIT_CODE)
         }
     }
 }
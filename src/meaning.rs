@@ -1,103 +1,323 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt, ops::Range};
 
 mod autogen;
 
-pub fn extract(node: &prism::Node) -> String {
+pub(crate) use autogen::node_name;
+
+/// Extracts the meaning of the code as a tree, ignoring the details formatting can change.
+pub fn extract(node: &prism::Node) -> Tree {
     let mut meaning = Meaning {
-        buffer: String::new(),
-        indent: 0,
+        stack: vec![Item::Field {
+            name: String::new(),
+            items: vec![],
+        }],
     };
     meaning.node(node);
-    meaning.buffer.trim().to_string()
+    match meaning.stack.pop() {
+        Some(Item::Field { items, .. }) => Tree { items },
+        item => unreachable!("unexpected meaning root: {:?}", item),
+    }
+}
+
+#[derive(Debug)]
+pub struct Tree {
+    items: Vec<Item>,
+}
+
+#[derive(Debug)]
+enum Item {
+    Node {
+        name: String,
+        // The byte range of the node in the source.
+        span: Range<usize>,
+        value: Option<String>,
+        items: Vec<Item>,
+    },
+    Field {
+        name: String,
+        items: Vec<Item>,
+    },
+    Value(String),
+    Content(String),
+}
+
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buffer = String::new();
+        for item in &self.items {
+            item.write(&mut buffer, 0);
+        }
+        write!(f, "{}", buffer.trim())
+    }
+}
+
+impl Item {
+    fn write(&self, buffer: &mut String, indent: usize) {
+        let spaces = " ".repeat(indent);
+        match self {
+            Self::Node {
+                name, value, items, ..
+            } => {
+                buffer.push_str(&format!("\n{spaces}[{name}]"));
+                if let Some(value) = value {
+                    buffer.push(' ');
+                    buffer.push_str(value);
+                }
+                for item in items {
+                    item.write(buffer, indent + 2);
+                }
+            }
+            Self::Field { name, items } => {
+                buffer.push_str(&format!("\n{spaces}{name}:"));
+                for item in items {
+                    item.write(buffer, indent + 2);
+                }
+            }
+            Self::Value(value) => buffer.push_str(&format!("\n{spaces}{value}")),
+            Self::Content(content) => buffer.push_str(&format!("\n---\n{content}\n---")),
+        }
+    }
+
+    fn summary(&self) -> String {
+        match self {
+            Self::Node {
+                name,
+                value: Some(value),
+                ..
+            } => format!("[{name}] {value}"),
+            Self::Node { name, .. } => format!("[{name}]"),
+            Self::Field { name, .. } => format!("{name}:"),
+            Self::Value(value) => value.clone(),
+            Self::Content(content) => format!("{:?}", content),
+        }
+    }
+}
+
+/// The first place where two meaning trees differ.
+#[derive(Debug)]
+pub struct Divergence {
+    /// The path to the differing item, like `ProgramNode.statements > StatementsNode.body[1]`.
+    pub path: String,
+    pub before: DivergentItem,
+    pub after: DivergentItem,
+}
+
+#[derive(Debug)]
+pub struct DivergentItem {
+    /// The byte range of the innermost node containing the difference.
+    pub span: Range<usize>,
+    pub summary: String,
+}
+
+pub fn first_divergence(before: &Tree, after: &Tree) -> Option<Divergence> {
+    let mut path = vec![];
+    let root_span = 0..0;
+    diverge_in(
+        &before.items,
+        &after.items,
+        &mut path,
+        (&root_span, &root_span),
+    )
+}
+
+enum PathSegment<'a> {
+    Node(&'a str),
+    Field(&'a str),
+}
+
+type Spans<'a> = (&'a Range<usize>, &'a Range<usize>);
+
+fn diverge_in<'a>(
+    before: &'a [Item],
+    after: &'a [Item],
+    path: &mut Vec<PathSegment<'a>>,
+    spans: Spans<'a>,
+) -> Option<Divergence> {
+    for i in 0..before.len().max(after.len()) {
+        let divergence = match (before.get(i), after.get(i)) {
+            (Some(b), Some(a)) => diverge_at(b, a, path, spans),
+            (b, a) => Some(divergence(path, spans, b, a)),
+        };
+        if divergence.is_some() {
+            return divergence;
+        }
+    }
+    None
+}
+
+fn diverge_at<'a>(
+    before: &'a Item,
+    after: &'a Item,
+    path: &mut Vec<PathSegment<'a>>,
+    spans: Spans<'a>,
+) -> Option<Divergence> {
+    match (before, after) {
+        (
+            Item::Node {
+                name: b_name,
+                span: b_span,
+                value: b_value,
+                items: b_items,
+            },
+            Item::Node {
+                name: a_name,
+                span: a_span,
+                value: a_value,
+                items: a_items,
+            },
+        ) => {
+            path.push(PathSegment::Node(b_name));
+            let spans = (b_span, a_span);
+            let result = if b_name != a_name || b_value != a_value {
+                Some(divergence(path, spans, Some(before), Some(after)))
+            } else {
+                diverge_in(b_items, a_items, path, spans)
+            };
+            path.pop();
+            result
+        }
+        (
+            Item::Field {
+                name: b_name,
+                items: b_items,
+            },
+            Item::Field {
+                name: a_name,
+                items: a_items,
+            },
+        ) if b_name == a_name => {
+            path.push(PathSegment::Field(b_name));
+            let result = diverge_in(b_items, a_items, path, spans);
+            path.pop();
+            result
+        }
+        (Item::Value(b), Item::Value(a)) | (Item::Content(b), Item::Content(a)) if b == a => None,
+        _ => Some(divergence(path, spans, Some(before), Some(after))),
+    }
+}
+
+fn divergence(
+    path: &[PathSegment],
+    spans: Spans,
+    before: Option<&Item>,
+    after: Option<&Item>,
+) -> Divergence {
+    let mut path_str = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Node(name) => {
+                if !path_str.is_empty() {
+                    path_str.push_str(" > ");
+                }
+                path_str.push_str(name);
+            }
+            PathSegment::Field(name) if name.bytes().all(|b| b.is_ascii_digit()) => {
+                path_str.push_str(&format!("[{name}]"));
+            }
+            PathSegment::Field(name) => {
+                path_str.push('.');
+                path_str.push_str(name);
+            }
+        }
+    }
+    let summary = |item: Option<&Item>| item.map_or("(nothing)".to_string(), |i| i.summary());
+    Divergence {
+        path: path_str,
+        before: DivergentItem {
+            span: spans.0.clone(),
+            summary: summary(before),
+        },
+        after: DivergentItem {
+            span: spans.1.clone(),
+            summary: summary(after),
+        },
+    }
 }
 
 struct Meaning {
-    buffer: String,
-    indent: usize,
+    // The nodes and fields being built. The first one is the root.
+    stack: Vec<Item>,
 }
 
 impl Meaning {
-    fn put_indent(&mut self) {
-        let spaces = " ".repeat(self.indent);
-        self.buffer.push_str(&spaces);
+    fn push(&mut self, item: Item) {
+        match self.stack.last_mut() {
+            Some(Item::Node { items, .. } | Item::Field { items, .. }) => items.push(item),
+            _ => unreachable!("meaning items must be put in a node or a field"),
+        }
     }
 
-    fn break_line(&mut self) {
-        self.buffer.push('\n');
+    fn close(&mut self) {
+        let item = self.stack.pop().expect("closing item must exist");
+        self.push(item);
     }
 
-    fn start_node(&mut self, name: &str) {
-        self.break_line();
-        self.put_indent();
-        self.buffer.push('[');
-        self.buffer.push_str(name);
-        self.buffer.push(']');
-        self.indent = self.indent.saturating_add(2);
+    fn start_node(&mut self, name: &str, loc: prism::Location) {
+        self.stack.push(Item::Node {
+            name: name.to_string(),
+            span: loc.start_offset()..loc.end_offset(),
+            value: None,
+            items: vec![],
+        });
     }
 
     fn end_node(&mut self) {
-        self.indent = self.indent.saturating_sub(2);
+        self.close();
     }
 
     fn atom_node(&mut self, name: &str, node: &prism::Node) {
-        self.break_line();
-        self.put_indent();
-        self.buffer.push('[');
-        self.buffer.push_str(name);
-        self.buffer.push_str("] ");
-        self.u8_bytes(node.location().as_slice().to_vec());
+        let loc = node.location();
+        self.push(Item::Node {
+            name: name.to_string(),
+            span: loc.start_offset()..loc.end_offset(),
+            value: Some(u8_bytes(&normalize_line_breaks(loc.as_slice()))),
+            items: vec![],
+        });
     }
 
-    fn numbered_parameters_node(&mut self, name: &str, _: prism::NumberedParametersNode) {
-        self.break_line();
-        self.put_indent();
-        self.buffer.push('[');
-        self.buffer.push_str(name);
-        self.buffer.push(']');
+    fn numbered_parameters_node(&mut self, name: &str, node: prism::NumberedParametersNode) {
+        self.start_node(name, node.location());
+        self.end_node();
     }
 
-    fn it_parameters_node(&mut self, name: &str, _: prism::ItParametersNode) {
-        self.break_line();
-        self.put_indent();
-        self.buffer.push('[');
-        self.buffer.push_str(name);
-        self.buffer.push(']');
+    fn it_parameters_node(&mut self, name: &str, node: prism::ItParametersNode) {
+        self.start_node(name, node.location());
+        self.end_node();
     }
 
     fn string_node(&mut self, name: &str, node: prism::StringNode) {
-        self.start_node(name);
+        self.start_node(name, node.location());
         self.string_or_heredoc(node.opening_loc(), node.content_loc());
         self.end_node();
     }
 
     fn x_string_node(&mut self, name: &str, node: prism::XStringNode) {
-        self.start_node(name);
+        self.start_node(name, node.location());
         self.string_or_heredoc(Some(node.opening_loc()), node.content_loc());
         self.end_node();
     }
 
     fn interpolated_string_node(&mut self, name: &str, node: prism::InterpolatedStringNode) {
-        self.start_node(name);
+        self.start_node(name, node.location());
         self.interpolated_string_or_heredoc(node.opening_loc(), node.parts());
         self.end_node();
     }
 
     fn interpolated_x_string_node(&mut self, name: &str, node: prism::InterpolatedXStringNode) {
-        self.start_node(name);
+        self.start_node(name, node.location());
         self.interpolated_string_or_heredoc(Some(node.opening_loc()), node.parts());
         self.end_node();
     }
 
     fn start_field(&mut self, name: impl ToString) {
-        self.break_line();
-        self.put_indent();
-        self.buffer.push_str(&name.to_string());
-        self.buffer.push(':');
-        self.indent = self.indent.saturating_add(2);
+        self.stack.push(Item::Field {
+            name: name.to_string(),
+            items: vec![],
+        });
     }
 
     fn end_field(&mut self) {
-        self.indent = self.indent.saturating_sub(2);
+        self.close();
     }
 
     fn node_field(&mut self, name: impl ToString, node: prism::Node) {
@@ -125,9 +345,7 @@ impl Meaning {
     fn opt_loc_field(&mut self, name: &str, loc: Option<prism::Location>) {
         self.start_field(name);
         if let Some(loc) = loc {
-            self.break_line();
-            self.put_indent();
-            self.u8_bytes(loc.as_slice().to_vec());
+            self.push(Item::Value(u8_bytes(loc.as_slice())));
         } else {
             self.none_value();
         }
@@ -141,9 +359,7 @@ impl Meaning {
             if matches!(bytes, [b':', b':']) {
                 bytes = &[b'.'];
             }
-            self.break_line();
-            self.put_indent();
-            self.u8_bytes(bytes.to_vec());
+            self.push(Item::Value(u8_bytes(bytes)));
         } else {
             self.none_value();
         }
@@ -166,9 +382,7 @@ impl Meaning {
         } else {
             "call".as_bytes()
         };
-        self.break_line();
-        self.put_indent();
-        self.u8_bytes(bytes.to_vec());
+        self.push(Item::Value(u8_bytes(bytes)));
         self.end_field();
     }
 
@@ -178,26 +392,12 @@ impl Meaning {
         }
     }
 
-    fn u8_bytes(&mut self, bytes: Vec<u8>) {
-        match String::from_utf8(bytes) {
-            Ok(value) => self.buffer.push_str(&value),
-            Err(err) => {
-                self.buffer.push_str(&format!("(non-utf8) {:?}", err));
-            }
-        }
-    }
-
     fn string_content(&mut self, value: Vec<u8>) {
-        self.break_line();
-        self.buffer.push_str("---\n");
-        self.u8_bytes(value);
-        self.buffer.push_str("\n---");
+        self.push(Item::Content(u8_bytes(&value)));
     }
 
     fn none_value(&mut self) {
-        self.break_line();
-        self.put_indent();
-        self.buffer.push_str("(none)");
+        self.push(Item::Value("(none)".to_string()));
     }
 
     fn string_or_heredoc(
@@ -205,15 +405,15 @@ impl Meaning {
         opening_loc: Option<prism::Location>,
         content_loc: prism::Location,
     ) {
+        let content = normalize_line_breaks(content_loc.as_slice());
         let content = if is_squiggly_heredoc(&opening_loc) {
-            content_loc
-                .as_slice()
+            content
                 .iter()
-                .skip_while(|c| **c == b' ')
+                .skip_while(|c| **c == b' ' || **c == b'\t')
                 .copied()
                 .collect()
         } else {
-            content_loc.as_slice().to_vec()
+            content
         };
         self.string_content(content);
     }
@@ -235,18 +435,13 @@ impl Meaning {
                 match part {
                     prism::Node::StringNode { .. } => {
                         let node = part.as_string_node().unwrap();
-                        let content = node.content_loc().as_slice();
+                        let content = normalize_line_breaks(node.content_loc().as_slice());
                         let content = if line_starts.contains(&i) {
-                            content
-                                .iter()
-                                .enumerate()
-                                .skip_while(|(i, c)| *i < indent_to_remove && **c == b' ')
-                                .map(|(_, c)| *c)
-                                .collect()
+                            dedent(&content, indent_to_remove).to_vec()
                         } else {
-                            content.to_vec()
+                            content
                         };
-                        self.start_node("StringNode");
+                        self.start_node("StringNode", node.location());
                         self.string_content(content);
                         self.end_node();
                     }
@@ -260,6 +455,25 @@ impl Meaning {
     }
 }
 
+// Ruby reads CRLF as LF even in string literals, so the line endings do not change the meaning.
+fn normalize_line_breaks(bytes: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(bytes.len());
+    for (i, b) in bytes.iter().enumerate() {
+        if *b == b'\r' && bytes.get(i + 1) == Some(&b'\n') {
+            continue;
+        }
+        normalized.push(*b);
+    }
+    normalized
+}
+
+fn u8_bytes(bytes: &[u8]) -> String {
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(value) => value,
+        Err(err) => format!("(non-utf8) {:?}", err),
+    }
+}
+
 fn is_squiggly_heredoc(opening_loc: &Option<prism::Location>) -> bool {
     if let Some(loc) = opening_loc {
         loc.as_slice().starts_with(b"<<~")
@@ -277,15 +491,15 @@ fn calc_squiggly_heredoc_indent(parts: &[prism::Node]) -> Option<(usize, HashSet
     let mut min_indent = usize::MAX;
     for (i, part) in parts.iter().enumerate() {
         if let Some(str) = part.as_string_node() {
-            let content = str.content_loc().as_slice();
+            let content = normalize_line_breaks(str.content_loc().as_slice());
             if is_line_start {
                 line_starts.insert(i);
                 let mut indent = 0;
                 let mut is_empty_line = false;
-                for ch in content {
+                for ch in &content {
                     match *ch {
-                        b'\t' => return None,
                         b' ' => indent += 1,
+                        b'\t' => indent = next_tab_stop(indent),
                         b'\n' => {
                             is_empty_line = true;
                             break;
@@ -310,3 +524,61 @@ fn calc_squiggly_heredoc_indent(parts: &[prism::Node]) -> Option<(usize, HashSet
     };
     Some((indent_to_remove, line_starts))
 }
+
+// Ruby counts a tab in the indentation of a squiggly heredoc up to the next multiple of 8.
+fn next_tab_stop(width: usize) -> usize {
+    (width / 8 + 1) * 8
+}
+
+// Removes the leading spaces and tabs up to the width, keeping a tab that goes beyond it.
+fn dedent(content: &[u8], width: usize) -> &[u8] {
+    let mut current = 0;
+    for (i, c) in content.iter().enumerate() {
+        current = match c {
+            b' ' => current + 1,
+            b'\t' => next_tab_stop(current),
+            _ => return &content[i..],
+        };
+        if current > width {
+            return &content[i..];
+        }
+    }
+    &content[content.len()..]
+}
+
+#[cfg(test)]
+mod test {
+    use similar_asserts::assert_eq;
+
+    #[test]
+    fn find_first_divergent_node() {
+        let before_src = b"class A\n  def b\n    foo(1, 2)\n  end\nend\n";
+        let after_src = b"class A\n  def b\n    foo(1,    3)\n  end\nend\n";
+        let before = super::extract(&prism::parse(before_src).node());
+        let after = super::extract(&prism::parse(after_src).node());
+
+        let divergence = super::first_divergence(&before, &after).expect("trees must differ");
+        assert_eq!(
+            divergence.path,
+            "ProgramNode.statements > StatementsNode.body[0] > ClassNode.body > StatementsNode.body[0] > DefNode.body > StatementsNode.body[0] > CallNode.arguments > ArgumentsNode.arguments[1] > IntegerNode"
+        );
+        assert_eq!(&before_src[divergence.before.span.clone()], b"2".as_slice());
+        assert_eq!(&after_src[divergence.after.span.clone()], b"3".as_slice());
+        assert_eq!(divergence.before.summary, "[IntegerNode] 2");
+        assert_eq!(divergence.after.summary, "[IntegerNode] 3");
+    }
+
+    #[test]
+    fn find_no_divergence_in_formatting_changes() {
+        let before = super::extract(&prism::parse(b"foo  . bar(1  ,2)").node());
+        let after = super::extract(&prism::parse(b"foo.bar(1, 2)\n").node());
+        assert!(super::first_divergence(&before, &after).is_none());
+    }
+
+    #[test]
+    fn dedent_squiggly_heredoc_indented_by_tabs() {
+        let before = super::extract(&prism::parse(b"<<~X\n  a\n    #{b}\nX\n").node());
+        let after = super::extract(&prism::parse(b"<<~X\n\t\ta\n\t\t  #{b}\n\tX\n").node());
+        assert!(super::first_divergence(&before, &after).is_none());
+    }
+}
@@ -4,7 +4,7 @@ impl super::Meaning {
         match node {
             prism::Node::AliasGlobalVariableNode { .. } => {
                 let node = node.as_alias_global_variable_node().unwrap();
-                self.start_node("AliasGlobalVariableNode");
+                self.start_node("AliasGlobalVariableNode", node.location());
                 self.node_field("new_name", node.new_name());
                 self.node_field("old_name", node.old_name());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
@@ -13,7 +13,7 @@ impl super::Meaning {
 
             prism::Node::AliasMethodNode { .. } => {
                 let node = node.as_alias_method_node().unwrap();
-                self.start_node("AliasMethodNode");
+                self.start_node("AliasMethodNode", node.location());
                 self.node_field("new_name", node.new_name());
                 self.node_field("old_name", node.old_name());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
@@ -22,7 +22,7 @@ impl super::Meaning {
 
             prism::Node::AlternationPatternNode { .. } => {
                 let node = node.as_alternation_pattern_node().unwrap();
-                self.start_node("AlternationPatternNode");
+                self.start_node("AlternationPatternNode", node.location());
                 self.node_field("left", node.left());
                 self.node_field("right", node.right());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -31,7 +31,7 @@ impl super::Meaning {
 
             prism::Node::AndNode { .. } => {
                 let node = node.as_and_node().unwrap();
-                self.start_node("AndNode");
+                self.start_node("AndNode", node.location());
                 self.node_field("left", node.left());
                 self.node_field("right", node.right());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -40,21 +40,21 @@ impl super::Meaning {
 
             prism::Node::ArgumentsNode { .. } => {
                 let node = node.as_arguments_node().unwrap();
-                self.start_node("ArgumentsNode");
+                self.start_node("ArgumentsNode", node.location());
                 self.list_field("arguments", node.arguments());
                 self.end_node();
             }
 
             prism::Node::ArrayNode { .. } => {
                 let node = node.as_array_node().unwrap();
-                self.start_node("ArrayNode");
+                self.start_node("ArrayNode", node.location());
                 self.list_field("elements", node.elements());
                 self.end_node();
             }
 
             prism::Node::ArrayPatternNode { .. } => {
                 let node = node.as_array_pattern_node().unwrap();
-                self.start_node("ArrayPatternNode");
+                self.start_node("ArrayPatternNode", node.location());
                 self.opt_field("constant", node.constant());
                 self.list_field("requireds", node.requireds());
                 self.opt_field("rest", node.rest());
@@ -64,7 +64,7 @@ impl super::Meaning {
 
             prism::Node::AssocNode { .. } => {
                 let node = node.as_assoc_node().unwrap();
-                self.start_node("AssocNode");
+                self.start_node("AssocNode", node.location());
                 self.node_field("key", node.key());
                 self.node_field("value", node.value());
                 self.opt_loc_field("operator_loc", node.operator_loc());
@@ -73,7 +73,7 @@ impl super::Meaning {
 
             prism::Node::AssocSplatNode { .. } => {
                 let node = node.as_assoc_splat_node().unwrap();
-                self.start_node("AssocSplatNode");
+                self.start_node("AssocSplatNode", node.location());
                 self.opt_field("value", node.value());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.end_node();
@@ -85,7 +85,7 @@ impl super::Meaning {
 
             prism::Node::BeginNode { .. } => {
                 let node = node.as_begin_node().unwrap();
-                self.start_node("BeginNode");
+                self.start_node("BeginNode", node.location());
                 self.opt_loc_field("begin_keyword_loc", node.begin_keyword_loc());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
                 self.opt_field("rescue_clause", node.rescue_clause().map(|n| n.as_node()));
@@ -97,7 +97,7 @@ impl super::Meaning {
 
             prism::Node::BlockArgumentNode { .. } => {
                 let node = node.as_block_argument_node().unwrap();
-                self.start_node("BlockArgumentNode");
+                self.start_node("BlockArgumentNode", node.location());
                 self.opt_field("expression", node.expression());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.end_node();
@@ -109,7 +109,7 @@ impl super::Meaning {
 
             prism::Node::BlockNode { .. } => {
                 let node = node.as_block_node().unwrap();
-                self.start_node("BlockNode");
+                self.start_node("BlockNode", node.location());
                 self.opt_field("parameters", node.parameters());
                 self.opt_field("body", node.body());
                 self.end_node();
@@ -117,7 +117,7 @@ impl super::Meaning {
 
             prism::Node::BlockParameterNode { .. } => {
                 let node = node.as_block_parameter_node().unwrap();
-                self.start_node("BlockParameterNode");
+                self.start_node("BlockParameterNode", node.location());
                 self.opt_loc_field("name_loc", node.name_loc());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.end_node();
@@ -125,7 +125,7 @@ impl super::Meaning {
 
             prism::Node::BlockParametersNode { .. } => {
                 let node = node.as_block_parameters_node().unwrap();
-                self.start_node("BlockParametersNode");
+                self.start_node("BlockParametersNode", node.location());
                 self.opt_field("parameters", node.parameters().map(|n| n.as_node()));
                 self.list_field("locals", node.locals());
                 self.end_node();
@@ -133,7 +133,7 @@ impl super::Meaning {
 
             prism::Node::BreakNode { .. } => {
                 let node = node.as_break_node().unwrap();
-                self.start_node("BreakNode");
+                self.start_node("BreakNode", node.location());
                 self.opt_field("arguments", node.arguments().map(|n| n.as_node()));
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.end_node();
@@ -141,7 +141,7 @@ impl super::Meaning {
 
             prism::Node::CallAndWriteNode { .. } => {
                 let node = node.as_call_and_write_node().unwrap();
-                self.start_node("CallAndWriteNode");
+                self.start_node("CallAndWriteNode", node.location());
                 self.opt_field("receiver", node.receiver());
                 self.call_operator_loc_field(node.call_operator_loc());
                 self.message_loc_field(node.message_loc());
@@ -152,7 +152,7 @@ impl super::Meaning {
 
             prism::Node::CallNode { .. } => {
                 let node = node.as_call_node().unwrap();
-                self.start_node("CallNode");
+                self.start_node("CallNode", node.location());
                 self.opt_field("receiver", node.receiver());
                 self.call_operator_loc_field(node.call_operator_loc());
                 self.message_loc_field(node.message_loc());
@@ -163,7 +163,7 @@ impl super::Meaning {
 
             prism::Node::CallOperatorWriteNode { .. } => {
                 let node = node.as_call_operator_write_node().unwrap();
-                self.start_node("CallOperatorWriteNode");
+                self.start_node("CallOperatorWriteNode", node.location());
                 self.opt_field("receiver", node.receiver());
                 self.call_operator_loc_field(node.call_operator_loc());
                 self.message_loc_field(node.message_loc());
@@ -174,7 +174,7 @@ impl super::Meaning {
 
             prism::Node::CallOrWriteNode { .. } => {
                 let node = node.as_call_or_write_node().unwrap();
-                self.start_node("CallOrWriteNode");
+                self.start_node("CallOrWriteNode", node.location());
                 self.opt_field("receiver", node.receiver());
                 self.call_operator_loc_field(node.call_operator_loc());
                 self.message_loc_field(node.message_loc());
@@ -185,7 +185,7 @@ impl super::Meaning {
 
             prism::Node::CallTargetNode { .. } => {
                 let node = node.as_call_target_node().unwrap();
-                self.start_node("CallTargetNode");
+                self.start_node("CallTargetNode", node.location());
                 self.node_field("receiver", node.receiver());
                 self.opt_loc_field("call_operator_loc", Some(node.call_operator_loc()));
                 self.message_loc_field(Some(node.message_loc()));
@@ -194,7 +194,7 @@ impl super::Meaning {
 
             prism::Node::CapturePatternNode { .. } => {
                 let node = node.as_capture_pattern_node().unwrap();
-                self.start_node("CapturePatternNode");
+                self.start_node("CapturePatternNode", node.location());
                 self.node_field("value", node.value());
                 self.node_field("target", node.target());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -203,7 +203,7 @@ impl super::Meaning {
 
             prism::Node::CaseMatchNode { .. } => {
                 let node = node.as_case_match_node().unwrap();
-                self.start_node("CaseMatchNode");
+                self.start_node("CaseMatchNode", node.location());
                 self.opt_field("predicate", node.predicate());
                 self.list_field("conditions", node.conditions());
                 self.opt_field("consequent", node.consequent().map(|n| n.as_node()));
@@ -214,7 +214,7 @@ impl super::Meaning {
 
             prism::Node::CaseNode { .. } => {
                 let node = node.as_case_node().unwrap();
-                self.start_node("CaseNode");
+                self.start_node("CaseNode", node.location());
                 self.opt_field("predicate", node.predicate());
                 self.list_field("conditions", node.conditions());
                 self.opt_field("consequent", node.consequent().map(|n| n.as_node()));
@@ -225,7 +225,7 @@ impl super::Meaning {
 
             prism::Node::ClassNode { .. } => {
                 let node = node.as_class_node().unwrap();
-                self.start_node("ClassNode");
+                self.start_node("ClassNode", node.location());
                 self.opt_loc_field("class_keyword_loc", Some(node.class_keyword_loc()));
                 self.node_field("constant_path", node.constant_path());
                 self.opt_loc_field("inheritance_operator_loc", node.inheritance_operator_loc());
@@ -237,7 +237,7 @@ impl super::Meaning {
 
             prism::Node::ClassVariableAndWriteNode { .. } => {
                 let node = node.as_class_variable_and_write_node().unwrap();
-                self.start_node("ClassVariableAndWriteNode");
+                self.start_node("ClassVariableAndWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -246,7 +246,7 @@ impl super::Meaning {
 
             prism::Node::ClassVariableOperatorWriteNode { .. } => {
                 let node = node.as_class_variable_operator_write_node().unwrap();
-                self.start_node("ClassVariableOperatorWriteNode");
+                self.start_node("ClassVariableOperatorWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("binary_operator_loc", Some(node.binary_operator_loc()));
                 self.node_field("value", node.value());
@@ -255,7 +255,7 @@ impl super::Meaning {
 
             prism::Node::ClassVariableOrWriteNode { .. } => {
                 let node = node.as_class_variable_or_write_node().unwrap();
-                self.start_node("ClassVariableOrWriteNode");
+                self.start_node("ClassVariableOrWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -272,7 +272,7 @@ impl super::Meaning {
 
             prism::Node::ClassVariableWriteNode { .. } => {
                 let node = node.as_class_variable_write_node().unwrap();
-                self.start_node("ClassVariableWriteNode");
+                self.start_node("ClassVariableWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.node_field("value", node.value());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -281,7 +281,7 @@ impl super::Meaning {
 
             prism::Node::ConstantAndWriteNode { .. } => {
                 let node = node.as_constant_and_write_node().unwrap();
-                self.start_node("ConstantAndWriteNode");
+                self.start_node("ConstantAndWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -290,7 +290,7 @@ impl super::Meaning {
 
             prism::Node::ConstantOperatorWriteNode { .. } => {
                 let node = node.as_constant_operator_write_node().unwrap();
-                self.start_node("ConstantOperatorWriteNode");
+                self.start_node("ConstantOperatorWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("binary_operator_loc", Some(node.binary_operator_loc()));
                 self.node_field("value", node.value());
@@ -299,7 +299,7 @@ impl super::Meaning {
 
             prism::Node::ConstantOrWriteNode { .. } => {
                 let node = node.as_constant_or_write_node().unwrap();
-                self.start_node("ConstantOrWriteNode");
+                self.start_node("ConstantOrWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -308,7 +308,7 @@ impl super::Meaning {
 
             prism::Node::ConstantPathAndWriteNode { .. } => {
                 let node = node.as_constant_path_and_write_node().unwrap();
-                self.start_node("ConstantPathAndWriteNode");
+                self.start_node("ConstantPathAndWriteNode", node.location());
                 self.node_field("target", node.target().as_node());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -317,7 +317,7 @@ impl super::Meaning {
 
             prism::Node::ConstantPathNode { .. } => {
                 let node = node.as_constant_path_node().unwrap();
-                self.start_node("ConstantPathNode");
+                self.start_node("ConstantPathNode", node.location());
                 self.opt_field("parent", node.parent());
                 self.opt_loc_field("delimiter_loc", Some(node.delimiter_loc()));
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
@@ -326,7 +326,7 @@ impl super::Meaning {
 
             prism::Node::ConstantPathOperatorWriteNode { .. } => {
                 let node = node.as_constant_path_operator_write_node().unwrap();
-                self.start_node("ConstantPathOperatorWriteNode");
+                self.start_node("ConstantPathOperatorWriteNode", node.location());
                 self.node_field("target", node.target().as_node());
                 self.opt_loc_field("binary_operator_loc", Some(node.binary_operator_loc()));
                 self.node_field("value", node.value());
@@ -335,7 +335,7 @@ impl super::Meaning {
 
             prism::Node::ConstantPathOrWriteNode { .. } => {
                 let node = node.as_constant_path_or_write_node().unwrap();
-                self.start_node("ConstantPathOrWriteNode");
+                self.start_node("ConstantPathOrWriteNode", node.location());
                 self.node_field("target", node.target().as_node());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -344,7 +344,7 @@ impl super::Meaning {
 
             prism::Node::ConstantPathTargetNode { .. } => {
                 let node = node.as_constant_path_target_node().unwrap();
-                self.start_node("ConstantPathTargetNode");
+                self.start_node("ConstantPathTargetNode", node.location());
                 self.opt_field("parent", node.parent());
                 self.opt_loc_field("delimiter_loc", Some(node.delimiter_loc()));
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
@@ -353,7 +353,7 @@ impl super::Meaning {
 
             prism::Node::ConstantPathWriteNode { .. } => {
                 let node = node.as_constant_path_write_node().unwrap();
-                self.start_node("ConstantPathWriteNode");
+                self.start_node("ConstantPathWriteNode", node.location());
                 self.node_field("target", node.target().as_node());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -370,7 +370,7 @@ impl super::Meaning {
 
             prism::Node::ConstantWriteNode { .. } => {
                 let node = node.as_constant_write_node().unwrap();
-                self.start_node("ConstantWriteNode");
+                self.start_node("ConstantWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.node_field("value", node.value());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -379,7 +379,7 @@ impl super::Meaning {
 
             prism::Node::DefNode { .. } => {
                 let node = node.as_def_node().unwrap();
-                self.start_node("DefNode");
+                self.start_node("DefNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_field("receiver", node.receiver());
                 self.opt_field("parameters", node.parameters().map(|n| n.as_node()));
@@ -393,7 +393,7 @@ impl super::Meaning {
 
             prism::Node::DefinedNode { .. } => {
                 let node = node.as_defined_node().unwrap();
-                self.start_node("DefinedNode");
+                self.start_node("DefinedNode", node.location());
                 self.opt_loc_field("lparen_loc", node.lparen_loc());
                 self.node_field("value", node.value());
                 self.opt_loc_field("rparen_loc", node.rparen_loc());
@@ -403,7 +403,7 @@ impl super::Meaning {
 
             prism::Node::ElseNode { .. } => {
                 let node = node.as_else_node().unwrap();
-                self.start_node("ElseNode");
+                self.start_node("ElseNode", node.location());
                 self.opt_loc_field("else_keyword_loc", Some(node.else_keyword_loc()));
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
                 self.opt_loc_field("end_keyword_loc", node.end_keyword_loc());
@@ -412,14 +412,14 @@ impl super::Meaning {
 
             prism::Node::EmbeddedStatementsNode { .. } => {
                 let node = node.as_embedded_statements_node().unwrap();
-                self.start_node("EmbeddedStatementsNode");
+                self.start_node("EmbeddedStatementsNode", node.location());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
                 self.end_node();
             }
 
             prism::Node::EmbeddedVariableNode { .. } => {
                 let node = node.as_embedded_variable_node().unwrap();
-                self.start_node("EmbeddedVariableNode");
+                self.start_node("EmbeddedVariableNode", node.location());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("variable", node.variable());
                 self.end_node();
@@ -427,7 +427,7 @@ impl super::Meaning {
 
             prism::Node::EnsureNode { .. } => {
                 let node = node.as_ensure_node().unwrap();
-                self.start_node("EnsureNode");
+                self.start_node("EnsureNode", node.location());
                 self.opt_loc_field("ensure_keyword_loc", Some(node.ensure_keyword_loc()));
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
                 self.opt_loc_field("end_keyword_loc", Some(node.end_keyword_loc()));
@@ -440,7 +440,7 @@ impl super::Meaning {
 
             prism::Node::FindPatternNode { .. } => {
                 let node = node.as_find_pattern_node().unwrap();
-                self.start_node("FindPatternNode");
+                self.start_node("FindPatternNode", node.location());
                 self.opt_field("constant", node.constant());
                 self.node_field("left", node.left());
                 self.list_field("requireds", node.requireds());
@@ -450,7 +450,7 @@ impl super::Meaning {
 
             prism::Node::FlipFlopNode { .. } => {
                 let node = node.as_flip_flop_node().unwrap();
-                self.start_node("FlipFlopNode");
+                self.start_node("FlipFlopNode", node.location());
                 self.opt_field("left", node.left());
                 self.opt_field("right", node.right());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -463,7 +463,7 @@ impl super::Meaning {
 
             prism::Node::ForNode { .. } => {
                 let node = node.as_for_node().unwrap();
-                self.start_node("ForNode");
+                self.start_node("ForNode", node.location());
                 self.node_field("index", node.index());
                 self.node_field("collection", node.collection());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
@@ -483,14 +483,14 @@ impl super::Meaning {
 
             prism::Node::ForwardingSuperNode { .. } => {
                 let node = node.as_forwarding_super_node().unwrap();
-                self.start_node("ForwardingSuperNode");
+                self.start_node("ForwardingSuperNode", node.location());
                 self.opt_field("block", node.block().map(|n| n.as_node()));
                 self.end_node();
             }
 
             prism::Node::GlobalVariableAndWriteNode { .. } => {
                 let node = node.as_global_variable_and_write_node().unwrap();
-                self.start_node("GlobalVariableAndWriteNode");
+                self.start_node("GlobalVariableAndWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -499,7 +499,7 @@ impl super::Meaning {
 
             prism::Node::GlobalVariableOperatorWriteNode { .. } => {
                 let node = node.as_global_variable_operator_write_node().unwrap();
-                self.start_node("GlobalVariableOperatorWriteNode");
+                self.start_node("GlobalVariableOperatorWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("binary_operator_loc", Some(node.binary_operator_loc()));
                 self.node_field("value", node.value());
@@ -508,7 +508,7 @@ impl super::Meaning {
 
             prism::Node::GlobalVariableOrWriteNode { .. } => {
                 let node = node.as_global_variable_or_write_node().unwrap();
-                self.start_node("GlobalVariableOrWriteNode");
+                self.start_node("GlobalVariableOrWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -525,7 +525,7 @@ impl super::Meaning {
 
             prism::Node::GlobalVariableWriteNode { .. } => {
                 let node = node.as_global_variable_write_node().unwrap();
-                self.start_node("GlobalVariableWriteNode");
+                self.start_node("GlobalVariableWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.node_field("value", node.value());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -534,14 +534,14 @@ impl super::Meaning {
 
             prism::Node::HashNode { .. } => {
                 let node = node.as_hash_node().unwrap();
-                self.start_node("HashNode");
+                self.start_node("HashNode", node.location());
                 self.list_field("elements", node.elements());
                 self.end_node();
             }
 
             prism::Node::HashPatternNode { .. } => {
                 let node = node.as_hash_pattern_node().unwrap();
-                self.start_node("HashPatternNode");
+                self.start_node("HashPatternNode", node.location());
                 self.opt_field("constant", node.constant());
                 self.list_field("elements", node.elements());
                 self.opt_field("rest", node.rest());
@@ -550,7 +550,7 @@ impl super::Meaning {
 
             prism::Node::IfNode { .. } => {
                 let node = node.as_if_node().unwrap();
-                self.start_node("IfNode");
+                self.start_node("IfNode", node.location());
                 self.opt_loc_field("if_keyword_loc", node.if_keyword_loc());
                 self.node_field("predicate", node.predicate());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
@@ -561,14 +561,14 @@ impl super::Meaning {
 
             prism::Node::ImaginaryNode { .. } => {
                 let node = node.as_imaginary_node().unwrap();
-                self.start_node("ImaginaryNode");
+                self.start_node("ImaginaryNode", node.location());
                 self.node_field("numeric", node.numeric());
                 self.end_node();
             }
 
             prism::Node::ImplicitNode { .. } => {
                 let node = node.as_implicit_node().unwrap();
-                self.start_node("ImplicitNode");
+                self.start_node("ImplicitNode", node.location());
                 self.node_field("value", node.value());
                 self.end_node();
             }
@@ -579,7 +579,7 @@ impl super::Meaning {
 
             prism::Node::InNode { .. } => {
                 let node = node.as_in_node().unwrap();
-                self.start_node("InNode");
+                self.start_node("InNode", node.location());
                 self.node_field("pattern", node.pattern());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
                 self.opt_loc_field("in_loc", Some(node.in_loc()));
@@ -588,7 +588,7 @@ impl super::Meaning {
 
             prism::Node::IndexAndWriteNode { .. } => {
                 let node = node.as_index_and_write_node().unwrap();
-                self.start_node("IndexAndWriteNode");
+                self.start_node("IndexAndWriteNode", node.location());
                 self.opt_field("receiver", node.receiver());
                 self.call_operator_loc_field(node.call_operator_loc());
                 self.opt_field("arguments", node.arguments().map(|n| n.as_node()));
@@ -600,7 +600,7 @@ impl super::Meaning {
 
             prism::Node::IndexOperatorWriteNode { .. } => {
                 let node = node.as_index_operator_write_node().unwrap();
-                self.start_node("IndexOperatorWriteNode");
+                self.start_node("IndexOperatorWriteNode", node.location());
                 self.opt_field("receiver", node.receiver());
                 self.call_operator_loc_field(node.call_operator_loc());
                 self.opt_field("arguments", node.arguments().map(|n| n.as_node()));
@@ -612,7 +612,7 @@ impl super::Meaning {
 
             prism::Node::IndexOrWriteNode { .. } => {
                 let node = node.as_index_or_write_node().unwrap();
-                self.start_node("IndexOrWriteNode");
+                self.start_node("IndexOrWriteNode", node.location());
                 self.opt_field("receiver", node.receiver());
                 self.call_operator_loc_field(node.call_operator_loc());
                 self.opt_field("arguments", node.arguments().map(|n| n.as_node()));
@@ -624,7 +624,7 @@ impl super::Meaning {
 
             prism::Node::IndexTargetNode { .. } => {
                 let node = node.as_index_target_node().unwrap();
-                self.start_node("IndexTargetNode");
+                self.start_node("IndexTargetNode", node.location());
                 self.node_field("receiver", node.receiver());
                 self.opt_field("arguments", node.arguments().map(|n| n.as_node()));
                 self.opt_field("block", node.block());
@@ -633,7 +633,7 @@ impl super::Meaning {
 
             prism::Node::InstanceVariableAndWriteNode { .. } => {
                 let node = node.as_instance_variable_and_write_node().unwrap();
-                self.start_node("InstanceVariableAndWriteNode");
+                self.start_node("InstanceVariableAndWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -642,7 +642,7 @@ impl super::Meaning {
 
             prism::Node::InstanceVariableOperatorWriteNode { .. } => {
                 let node = node.as_instance_variable_operator_write_node().unwrap();
-                self.start_node("InstanceVariableOperatorWriteNode");
+                self.start_node("InstanceVariableOperatorWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("binary_operator_loc", Some(node.binary_operator_loc()));
                 self.node_field("value", node.value());
@@ -651,7 +651,7 @@ impl super::Meaning {
 
             prism::Node::InstanceVariableOrWriteNode { .. } => {
                 let node = node.as_instance_variable_or_write_node().unwrap();
-                self.start_node("InstanceVariableOrWriteNode");
+                self.start_node("InstanceVariableOrWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -668,7 +668,7 @@ impl super::Meaning {
 
             prism::Node::InstanceVariableWriteNode { .. } => {
                 let node = node.as_instance_variable_write_node().unwrap();
-                self.start_node("InstanceVariableWriteNode");
+                self.start_node("InstanceVariableWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.node_field("value", node.value());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -681,14 +681,14 @@ impl super::Meaning {
 
             prism::Node::InterpolatedMatchLastLineNode { .. } => {
                 let node = node.as_interpolated_match_last_line_node().unwrap();
-                self.start_node("InterpolatedMatchLastLineNode");
+                self.start_node("InterpolatedMatchLastLineNode", node.location());
                 self.list_field("parts", node.parts());
                 self.end_node();
             }
 
             prism::Node::InterpolatedRegularExpressionNode { .. } => {
                 let node = node.as_interpolated_regular_expression_node().unwrap();
-                self.start_node("InterpolatedRegularExpressionNode");
+                self.start_node("InterpolatedRegularExpressionNode", node.location());
                 self.list_field("parts", node.parts());
                 self.end_node();
             }
@@ -700,7 +700,7 @@ impl super::Meaning {
 
             prism::Node::InterpolatedSymbolNode { .. } => {
                 let node = node.as_interpolated_symbol_node().unwrap();
-                self.start_node("InterpolatedSymbolNode");
+                self.start_node("InterpolatedSymbolNode", node.location());
                 self.list_field("parts", node.parts());
                 self.end_node();
             }
@@ -721,14 +721,14 @@ impl super::Meaning {
 
             prism::Node::KeywordHashNode { .. } => {
                 let node = node.as_keyword_hash_node().unwrap();
-                self.start_node("KeywordHashNode");
+                self.start_node("KeywordHashNode", node.location());
                 self.list_field("elements", node.elements());
                 self.end_node();
             }
 
             prism::Node::KeywordRestParameterNode { .. } => {
                 let node = node.as_keyword_rest_parameter_node().unwrap();
-                self.start_node("KeywordRestParameterNode");
+                self.start_node("KeywordRestParameterNode", node.location());
                 self.opt_loc_field("name_loc", node.name_loc());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.end_node();
@@ -736,7 +736,7 @@ impl super::Meaning {
 
             prism::Node::LambdaNode { .. } => {
                 let node = node.as_lambda_node().unwrap();
-                self.start_node("LambdaNode");
+                self.start_node("LambdaNode", node.location());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.opt_field("parameters", node.parameters());
                 self.opt_field("body", node.body());
@@ -745,7 +745,7 @@ impl super::Meaning {
 
             prism::Node::LocalVariableAndWriteNode { .. } => {
                 let node = node.as_local_variable_and_write_node().unwrap();
-                self.start_node("LocalVariableAndWriteNode");
+                self.start_node("LocalVariableAndWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -754,7 +754,7 @@ impl super::Meaning {
 
             prism::Node::LocalVariableOperatorWriteNode { .. } => {
                 let node = node.as_local_variable_operator_write_node().unwrap();
-                self.start_node("LocalVariableOperatorWriteNode");
+                self.start_node("LocalVariableOperatorWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("binary_operator_loc", Some(node.binary_operator_loc()));
                 self.node_field("value", node.value());
@@ -763,7 +763,7 @@ impl super::Meaning {
 
             prism::Node::LocalVariableOrWriteNode { .. } => {
                 let node = node.as_local_variable_or_write_node().unwrap();
-                self.start_node("LocalVariableOrWriteNode");
+                self.start_node("LocalVariableOrWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -780,7 +780,7 @@ impl super::Meaning {
 
             prism::Node::LocalVariableWriteNode { .. } => {
                 let node = node.as_local_variable_write_node().unwrap();
-                self.start_node("LocalVariableWriteNode");
+                self.start_node("LocalVariableWriteNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.node_field("value", node.value());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -789,14 +789,14 @@ impl super::Meaning {
 
             prism::Node::MatchLastLineNode { .. } => {
                 let node = node.as_match_last_line_node().unwrap();
-                self.start_node("MatchLastLineNode");
+                self.start_node("MatchLastLineNode", node.location());
                 self.string_content(node.content_loc().as_slice().to_vec());
                 self.end_node();
             }
 
             prism::Node::MatchPredicateNode { .. } => {
                 let node = node.as_match_predicate_node().unwrap();
-                self.start_node("MatchPredicateNode");
+                self.start_node("MatchPredicateNode", node.location());
                 self.node_field("value", node.value());
                 self.node_field("pattern", node.pattern());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -805,7 +805,7 @@ impl super::Meaning {
 
             prism::Node::MatchRequiredNode { .. } => {
                 let node = node.as_match_required_node().unwrap();
-                self.start_node("MatchRequiredNode");
+                self.start_node("MatchRequiredNode", node.location());
                 self.node_field("value", node.value());
                 self.node_field("pattern", node.pattern());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -814,7 +814,7 @@ impl super::Meaning {
 
             prism::Node::MatchWriteNode { .. } => {
                 let node = node.as_match_write_node().unwrap();
-                self.start_node("MatchWriteNode");
+                self.start_node("MatchWriteNode", node.location());
                 self.node_field("call", node.call().as_node());
                 self.list_field("targets", node.targets());
                 self.end_node();
@@ -826,7 +826,7 @@ impl super::Meaning {
 
             prism::Node::ModuleNode { .. } => {
                 let node = node.as_module_node().unwrap();
-                self.start_node("ModuleNode");
+                self.start_node("ModuleNode", node.location());
                 self.opt_loc_field("module_keyword_loc", Some(node.module_keyword_loc()));
                 self.node_field("constant_path", node.constant_path());
                 self.opt_field("body", node.body());
@@ -836,7 +836,7 @@ impl super::Meaning {
 
             prism::Node::MultiTargetNode { .. } => {
                 let node = node.as_multi_target_node().unwrap();
-                self.start_node("MultiTargetNode");
+                self.start_node("MultiTargetNode", node.location());
                 self.list_field("lefts", node.lefts());
                 self.opt_field("rest", node.rest());
                 self.list_field("rights", node.rights());
@@ -847,7 +847,7 @@ impl super::Meaning {
 
             prism::Node::MultiWriteNode { .. } => {
                 let node = node.as_multi_write_node().unwrap();
-                self.start_node("MultiWriteNode");
+                self.start_node("MultiWriteNode", node.location());
                 self.list_field("lefts", node.lefts());
                 self.opt_field("rest", node.rest());
                 self.list_field("rights", node.rights());
@@ -858,7 +858,7 @@ impl super::Meaning {
 
             prism::Node::NextNode { .. } => {
                 let node = node.as_next_node().unwrap();
-                self.start_node("NextNode");
+                self.start_node("NextNode", node.location());
                 self.opt_field("arguments", node.arguments().map(|n| n.as_node()));
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.end_node();
@@ -870,7 +870,7 @@ impl super::Meaning {
 
             prism::Node::NoKeywordsParameterNode { .. } => {
                 let node = node.as_no_keywords_parameter_node().unwrap();
-                self.start_node("NoKeywordsParameterNode");
+                self.start_node("NoKeywordsParameterNode", node.location());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.end_node();
@@ -887,7 +887,7 @@ impl super::Meaning {
 
             prism::Node::OptionalKeywordParameterNode { .. } => {
                 let node = node.as_optional_keyword_parameter_node().unwrap();
-                self.start_node("OptionalKeywordParameterNode");
+                self.start_node("OptionalKeywordParameterNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.node_field("value", node.value());
                 self.end_node();
@@ -895,7 +895,7 @@ impl super::Meaning {
 
             prism::Node::OptionalParameterNode { .. } => {
                 let node = node.as_optional_parameter_node().unwrap();
-                self.start_node("OptionalParameterNode");
+                self.start_node("OptionalParameterNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("value", node.value());
@@ -904,7 +904,7 @@ impl super::Meaning {
 
             prism::Node::OrNode { .. } => {
                 let node = node.as_or_node().unwrap();
-                self.start_node("OrNode");
+                self.start_node("OrNode", node.location());
                 self.node_field("left", node.left());
                 self.node_field("right", node.right());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -913,7 +913,7 @@ impl super::Meaning {
 
             prism::Node::ParametersNode { .. } => {
                 let node = node.as_parameters_node().unwrap();
-                self.start_node("ParametersNode");
+                self.start_node("ParametersNode", node.location());
                 self.list_field("requireds", node.requireds());
                 self.list_field("optionals", node.optionals());
                 self.opt_field("rest", node.rest());
@@ -926,14 +926,14 @@ impl super::Meaning {
 
             prism::Node::ParenthesesNode { .. } => {
                 let node = node.as_parentheses_node().unwrap();
-                self.start_node("ParenthesesNode");
+                self.start_node("ParenthesesNode", node.location());
                 self.opt_field("body", node.body());
                 self.end_node();
             }
 
             prism::Node::PinnedExpressionNode { .. } => {
                 let node = node.as_pinned_expression_node().unwrap();
-                self.start_node("PinnedExpressionNode");
+                self.start_node("PinnedExpressionNode", node.location());
                 self.node_field("expression", node.expression());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.opt_loc_field("lparen_loc", Some(node.lparen_loc()));
@@ -943,7 +943,7 @@ impl super::Meaning {
 
             prism::Node::PinnedVariableNode { .. } => {
                 let node = node.as_pinned_variable_node().unwrap();
-                self.start_node("PinnedVariableNode");
+                self.start_node("PinnedVariableNode", node.location());
                 self.node_field("variable", node.variable());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.end_node();
@@ -951,7 +951,7 @@ impl super::Meaning {
 
             prism::Node::PostExecutionNode { .. } => {
                 let node = node.as_post_execution_node().unwrap();
-                self.start_node("PostExecutionNode");
+                self.start_node("PostExecutionNode", node.location());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.end_node();
@@ -959,7 +959,7 @@ impl super::Meaning {
 
             prism::Node::PreExecutionNode { .. } => {
                 let node = node.as_pre_execution_node().unwrap();
-                self.start_node("PreExecutionNode");
+                self.start_node("PreExecutionNode", node.location());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.end_node();
@@ -967,14 +967,14 @@ impl super::Meaning {
 
             prism::Node::ProgramNode { .. } => {
                 let node = node.as_program_node().unwrap();
-                self.start_node("ProgramNode");
+                self.start_node("ProgramNode", node.location());
                 self.node_field("statements", node.statements().as_node());
                 self.end_node();
             }
 
             prism::Node::RangeNode { .. } => {
                 let node = node.as_range_node().unwrap();
-                self.start_node("RangeNode");
+                self.start_node("RangeNode", node.location());
                 self.opt_field("left", node.left());
                 self.opt_field("right", node.right());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
@@ -991,14 +991,14 @@ impl super::Meaning {
 
             prism::Node::RegularExpressionNode { .. } => {
                 let node = node.as_regular_expression_node().unwrap();
-                self.start_node("RegularExpressionNode");
+                self.start_node("RegularExpressionNode", node.location());
                 self.string_content(node.content_loc().as_slice().to_vec());
                 self.end_node();
             }
 
             prism::Node::RequiredKeywordParameterNode { .. } => {
                 let node = node.as_required_keyword_parameter_node().unwrap();
-                self.start_node("RequiredKeywordParameterNode");
+                self.start_node("RequiredKeywordParameterNode", node.location());
                 self.opt_loc_field("name_loc", Some(node.name_loc()));
                 self.end_node();
             }
@@ -1009,7 +1009,7 @@ impl super::Meaning {
 
             prism::Node::RescueModifierNode { .. } => {
                 let node = node.as_rescue_modifier_node().unwrap();
-                self.start_node("RescueModifierNode");
+                self.start_node("RescueModifierNode", node.location());
                 self.node_field("expression", node.expression());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.node_field("rescue_expression", node.rescue_expression());
@@ -1018,7 +1018,7 @@ impl super::Meaning {
 
             prism::Node::RescueNode { .. } => {
                 let node = node.as_rescue_node().unwrap();
-                self.start_node("RescueNode");
+                self.start_node("RescueNode", node.location());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.list_field("exceptions", node.exceptions());
                 self.opt_loc_field("operator_loc", node.operator_loc());
@@ -1030,7 +1030,7 @@ impl super::Meaning {
 
             prism::Node::RestParameterNode { .. } => {
                 let node = node.as_rest_parameter_node().unwrap();
-                self.start_node("RestParameterNode");
+                self.start_node("RestParameterNode", node.location());
                 self.opt_loc_field("name_loc", node.name_loc());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.end_node();
@@ -1042,7 +1042,7 @@ impl super::Meaning {
 
             prism::Node::ReturnNode { .. } => {
                 let node = node.as_return_node().unwrap();
-                self.start_node("ReturnNode");
+                self.start_node("ReturnNode", node.location());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.opt_field("arguments", node.arguments().map(|n| n.as_node()));
                 self.end_node();
@@ -1054,14 +1054,14 @@ impl super::Meaning {
 
             prism::Node::ShareableConstantNode { .. } => {
                 let node = node.as_shareable_constant_node().unwrap();
-                self.start_node("ShareableConstantNode");
+                self.start_node("ShareableConstantNode", node.location());
                 self.node_field("write", node.write());
                 self.end_node();
             }
 
             prism::Node::SingletonClassNode { .. } => {
                 let node = node.as_singleton_class_node().unwrap();
-                self.start_node("SingletonClassNode");
+                self.start_node("SingletonClassNode", node.location());
                 self.opt_loc_field("class_keyword_loc", Some(node.class_keyword_loc()));
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.node_field("expression", node.expression());
@@ -1084,7 +1084,7 @@ impl super::Meaning {
 
             prism::Node::SplatNode { .. } => {
                 let node = node.as_splat_node().unwrap();
-                self.start_node("SplatNode");
+                self.start_node("SplatNode", node.location());
                 self.opt_loc_field("operator_loc", Some(node.operator_loc()));
                 self.opt_field("expression", node.expression());
                 self.end_node();
@@ -1092,7 +1092,7 @@ impl super::Meaning {
 
             prism::Node::StatementsNode { .. } => {
                 let node = node.as_statements_node().unwrap();
-                self.start_node("StatementsNode");
+                self.start_node("StatementsNode", node.location());
                 self.list_field("body", node.body());
                 self.end_node();
             }
@@ -1104,7 +1104,7 @@ impl super::Meaning {
 
             prism::Node::SuperNode { .. } => {
                 let node = node.as_super_node().unwrap();
-                self.start_node("SuperNode");
+                self.start_node("SuperNode", node.location());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.opt_loc_field("lparen_loc", node.lparen_loc());
                 self.opt_field("arguments", node.arguments().map(|n| n.as_node()));
@@ -1115,7 +1115,7 @@ impl super::Meaning {
 
             prism::Node::SymbolNode { .. } => {
                 let node = node.as_symbol_node().unwrap();
-                self.start_node("SymbolNode");
+                self.start_node("SymbolNode", node.location());
                 self.opt_loc_field("value_loc", node.value_loc());
                 self.end_node();
             }
@@ -1126,7 +1126,7 @@ impl super::Meaning {
 
             prism::Node::UndefNode { .. } => {
                 let node = node.as_undef_node().unwrap();
-                self.start_node("UndefNode");
+                self.start_node("UndefNode", node.location());
                 self.list_field("names", node.names());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.end_node();
@@ -1134,7 +1134,7 @@ impl super::Meaning {
 
             prism::Node::UnlessNode { .. } => {
                 let node = node.as_unless_node().unwrap();
-                self.start_node("UnlessNode");
+                self.start_node("UnlessNode", node.location());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.node_field("predicate", node.predicate());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
@@ -1145,7 +1145,7 @@ impl super::Meaning {
 
             prism::Node::UntilNode { .. } => {
                 let node = node.as_until_node().unwrap();
-                self.start_node("UntilNode");
+                self.start_node("UntilNode", node.location());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.node_field("predicate", node.predicate());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
@@ -1154,7 +1154,7 @@ impl super::Meaning {
 
             prism::Node::WhenNode { .. } => {
                 let node = node.as_when_node().unwrap();
-                self.start_node("WhenNode");
+                self.start_node("WhenNode", node.location());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.list_field("conditions", node.conditions());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
@@ -1163,7 +1163,7 @@ impl super::Meaning {
 
             prism::Node::WhileNode { .. } => {
                 let node = node.as_while_node().unwrap();
-                self.start_node("WhileNode");
+                self.start_node("WhileNode", node.location());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.node_field("predicate", node.predicate());
                 self.opt_field("statements", node.statements().map(|n| n.as_node()));
@@ -1177,7 +1177,7 @@ impl super::Meaning {
 
             prism::Node::YieldNode { .. } => {
                 let node = node.as_yield_node().unwrap();
-                self.start_node("YieldNode");
+                self.start_node("YieldNode", node.location());
                 self.opt_loc_field("keyword_loc", Some(node.keyword_loc()));
                 self.opt_loc_field("lparen_loc", node.lparen_loc());
                 self.opt_field("arguments", node.arguments().map(|n| n.as_node()));
@@ -1187,3 +1187,163 @@ impl super::Meaning {
         }
     }
 }
+
+pub(crate) fn node_name(node: &prism::Node) -> &'static str {
+    match node {
+        prism::Node::AliasGlobalVariableNode { .. } => "AliasGlobalVariableNode",
+        prism::Node::AliasMethodNode { .. } => "AliasMethodNode",
+        prism::Node::AlternationPatternNode { .. } => "AlternationPatternNode",
+        prism::Node::AndNode { .. } => "AndNode",
+        prism::Node::ArgumentsNode { .. } => "ArgumentsNode",
+        prism::Node::ArrayNode { .. } => "ArrayNode",
+        prism::Node::ArrayPatternNode { .. } => "ArrayPatternNode",
+        prism::Node::AssocNode { .. } => "AssocNode",
+        prism::Node::AssocSplatNode { .. } => "AssocSplatNode",
+        prism::Node::BackReferenceReadNode { .. } => "BackReferenceReadNode",
+        prism::Node::BeginNode { .. } => "BeginNode",
+        prism::Node::BlockArgumentNode { .. } => "BlockArgumentNode",
+        prism::Node::BlockLocalVariableNode { .. } => "BlockLocalVariableNode",
+        prism::Node::BlockNode { .. } => "BlockNode",
+        prism::Node::BlockParameterNode { .. } => "BlockParameterNode",
+        prism::Node::BlockParametersNode { .. } => "BlockParametersNode",
+        prism::Node::BreakNode { .. } => "BreakNode",
+        prism::Node::CallAndWriteNode { .. } => "CallAndWriteNode",
+        prism::Node::CallNode { .. } => "CallNode",
+        prism::Node::CallOperatorWriteNode { .. } => "CallOperatorWriteNode",
+        prism::Node::CallOrWriteNode { .. } => "CallOrWriteNode",
+        prism::Node::CallTargetNode { .. } => "CallTargetNode",
+        prism::Node::CapturePatternNode { .. } => "CapturePatternNode",
+        prism::Node::CaseMatchNode { .. } => "CaseMatchNode",
+        prism::Node::CaseNode { .. } => "CaseNode",
+        prism::Node::ClassNode { .. } => "ClassNode",
+        prism::Node::ClassVariableAndWriteNode { .. } => "ClassVariableAndWriteNode",
+        prism::Node::ClassVariableOperatorWriteNode { .. } => "ClassVariableOperatorWriteNode",
+        prism::Node::ClassVariableOrWriteNode { .. } => "ClassVariableOrWriteNode",
+        prism::Node::ClassVariableReadNode { .. } => "ClassVariableReadNode",
+        prism::Node::ClassVariableTargetNode { .. } => "ClassVariableTargetNode",
+        prism::Node::ClassVariableWriteNode { .. } => "ClassVariableWriteNode",
+        prism::Node::ConstantAndWriteNode { .. } => "ConstantAndWriteNode",
+        prism::Node::ConstantOperatorWriteNode { .. } => "ConstantOperatorWriteNode",
+        prism::Node::ConstantOrWriteNode { .. } => "ConstantOrWriteNode",
+        prism::Node::ConstantPathAndWriteNode { .. } => "ConstantPathAndWriteNode",
+        prism::Node::ConstantPathNode { .. } => "ConstantPathNode",
+        prism::Node::ConstantPathOperatorWriteNode { .. } => "ConstantPathOperatorWriteNode",
+        prism::Node::ConstantPathOrWriteNode { .. } => "ConstantPathOrWriteNode",
+        prism::Node::ConstantPathTargetNode { .. } => "ConstantPathTargetNode",
+        prism::Node::ConstantPathWriteNode { .. } => "ConstantPathWriteNode",
+        prism::Node::ConstantReadNode { .. } => "ConstantReadNode",
+        prism::Node::ConstantTargetNode { .. } => "ConstantTargetNode",
+        prism::Node::ConstantWriteNode { .. } => "ConstantWriteNode",
+        prism::Node::DefNode { .. } => "DefNode",
+        prism::Node::DefinedNode { .. } => "DefinedNode",
+        prism::Node::ElseNode { .. } => "ElseNode",
+        prism::Node::EmbeddedStatementsNode { .. } => "EmbeddedStatementsNode",
+        prism::Node::EmbeddedVariableNode { .. } => "EmbeddedVariableNode",
+        prism::Node::EnsureNode { .. } => "EnsureNode",
+        prism::Node::FalseNode { .. } => "FalseNode",
+        prism::Node::FindPatternNode { .. } => "FindPatternNode",
+        prism::Node::FlipFlopNode { .. } => "FlipFlopNode",
+        prism::Node::FloatNode { .. } => "FloatNode",
+        prism::Node::ForNode { .. } => "ForNode",
+        prism::Node::ForwardingArgumentsNode { .. } => "ForwardingArgumentsNode",
+        prism::Node::ForwardingParameterNode { .. } => "ForwardingParameterNode",
+        prism::Node::ForwardingSuperNode { .. } => "ForwardingSuperNode",
+        prism::Node::GlobalVariableAndWriteNode { .. } => "GlobalVariableAndWriteNode",
+        prism::Node::GlobalVariableOperatorWriteNode { .. } => "GlobalVariableOperatorWriteNode",
+        prism::Node::GlobalVariableOrWriteNode { .. } => "GlobalVariableOrWriteNode",
+        prism::Node::GlobalVariableReadNode { .. } => "GlobalVariableReadNode",
+        prism::Node::GlobalVariableTargetNode { .. } => "GlobalVariableTargetNode",
+        prism::Node::GlobalVariableWriteNode { .. } => "GlobalVariableWriteNode",
+        prism::Node::HashNode { .. } => "HashNode",
+        prism::Node::HashPatternNode { .. } => "HashPatternNode",
+        prism::Node::IfNode { .. } => "IfNode",
+        prism::Node::ImaginaryNode { .. } => "ImaginaryNode",
+        prism::Node::ImplicitNode { .. } => "ImplicitNode",
+        prism::Node::ImplicitRestNode { .. } => "ImplicitRestNode",
+        prism::Node::InNode { .. } => "InNode",
+        prism::Node::IndexAndWriteNode { .. } => "IndexAndWriteNode",
+        prism::Node::IndexOperatorWriteNode { .. } => "IndexOperatorWriteNode",
+        prism::Node::IndexOrWriteNode { .. } => "IndexOrWriteNode",
+        prism::Node::IndexTargetNode { .. } => "IndexTargetNode",
+        prism::Node::InstanceVariableAndWriteNode { .. } => "InstanceVariableAndWriteNode",
+        prism::Node::InstanceVariableOperatorWriteNode { .. } => {
+            "InstanceVariableOperatorWriteNode"
+        }
+        prism::Node::InstanceVariableOrWriteNode { .. } => "InstanceVariableOrWriteNode",
+        prism::Node::InstanceVariableReadNode { .. } => "InstanceVariableReadNode",
+        prism::Node::InstanceVariableTargetNode { .. } => "InstanceVariableTargetNode",
+        prism::Node::InstanceVariableWriteNode { .. } => "InstanceVariableWriteNode",
+        prism::Node::IntegerNode { .. } => "IntegerNode",
+        prism::Node::InterpolatedMatchLastLineNode { .. } => "InterpolatedMatchLastLineNode",
+        prism::Node::InterpolatedRegularExpressionNode { .. } => {
+            "InterpolatedRegularExpressionNode"
+        }
+        prism::Node::InterpolatedStringNode { .. } => "InterpolatedStringNode",
+        prism::Node::InterpolatedSymbolNode { .. } => "InterpolatedSymbolNode",
+        prism::Node::InterpolatedXStringNode { .. } => "InterpolatedXStringNode",
+        prism::Node::ItLocalVariableReadNode { .. } => "ItLocalVariableReadNode",
+        prism::Node::ItParametersNode { .. } => "ItParametersNode",
+        prism::Node::KeywordHashNode { .. } => "KeywordHashNode",
+        prism::Node::KeywordRestParameterNode { .. } => "KeywordRestParameterNode",
+        prism::Node::LambdaNode { .. } => "LambdaNode",
+        prism::Node::LocalVariableAndWriteNode { .. } => "LocalVariableAndWriteNode",
+        prism::Node::LocalVariableOperatorWriteNode { .. } => "LocalVariableOperatorWriteNode",
+        prism::Node::LocalVariableOrWriteNode { .. } => "LocalVariableOrWriteNode",
+        prism::Node::LocalVariableReadNode { .. } => "LocalVariableReadNode",
+        prism::Node::LocalVariableTargetNode { .. } => "LocalVariableTargetNode",
+        prism::Node::LocalVariableWriteNode { .. } => "LocalVariableWriteNode",
+        prism::Node::MatchLastLineNode { .. } => "MatchLastLineNode",
+        prism::Node::MatchPredicateNode { .. } => "MatchPredicateNode",
+        prism::Node::MatchRequiredNode { .. } => "MatchRequiredNode",
+        prism::Node::MatchWriteNode { .. } => "MatchWriteNode",
+        prism::Node::MissingNode { .. } => "MissingNode",
+        prism::Node::ModuleNode { .. } => "ModuleNode",
+        prism::Node::MultiTargetNode { .. } => "MultiTargetNode",
+        prism::Node::MultiWriteNode { .. } => "MultiWriteNode",
+        prism::Node::NextNode { .. } => "NextNode",
+        prism::Node::NilNode { .. } => "NilNode",
+        prism::Node::NoKeywordsParameterNode { .. } => "NoKeywordsParameterNode",
+        prism::Node::NumberedParametersNode { .. } => "NumberedParametersNode",
+        prism::Node::NumberedReferenceReadNode { .. } => "NumberedReferenceReadNode",
+        prism::Node::OptionalKeywordParameterNode { .. } => "OptionalKeywordParameterNode",
+        prism::Node::OptionalParameterNode { .. } => "OptionalParameterNode",
+        prism::Node::OrNode { .. } => "OrNode",
+        prism::Node::ParametersNode { .. } => "ParametersNode",
+        prism::Node::ParenthesesNode { .. } => "ParenthesesNode",
+        prism::Node::PinnedExpressionNode { .. } => "PinnedExpressionNode",
+        prism::Node::PinnedVariableNode { .. } => "PinnedVariableNode",
+        prism::Node::PostExecutionNode { .. } => "PostExecutionNode",
+        prism::Node::PreExecutionNode { .. } => "PreExecutionNode",
+        prism::Node::ProgramNode { .. } => "ProgramNode",
+        prism::Node::RangeNode { .. } => "RangeNode",
+        prism::Node::RationalNode { .. } => "RationalNode",
+        prism::Node::RedoNode { .. } => "RedoNode",
+        prism::Node::RegularExpressionNode { .. } => "RegularExpressionNode",
+        prism::Node::RequiredKeywordParameterNode { .. } => "RequiredKeywordParameterNode",
+        prism::Node::RequiredParameterNode { .. } => "RequiredParameterNode",
+        prism::Node::RescueModifierNode { .. } => "RescueModifierNode",
+        prism::Node::RescueNode { .. } => "RescueNode",
+        prism::Node::RestParameterNode { .. } => "RestParameterNode",
+        prism::Node::RetryNode { .. } => "RetryNode",
+        prism::Node::ReturnNode { .. } => "ReturnNode",
+        prism::Node::SelfNode { .. } => "SelfNode",
+        prism::Node::ShareableConstantNode { .. } => "ShareableConstantNode",
+        prism::Node::SingletonClassNode { .. } => "SingletonClassNode",
+        prism::Node::SourceEncodingNode { .. } => "SourceEncodingNode",
+        prism::Node::SourceFileNode { .. } => "SourceFileNode",
+        prism::Node::SourceLineNode { .. } => "SourceLineNode",
+        prism::Node::SplatNode { .. } => "SplatNode",
+        prism::Node::StatementsNode { .. } => "StatementsNode",
+        prism::Node::StringNode { .. } => "StringNode",
+        prism::Node::SuperNode { .. } => "SuperNode",
+        prism::Node::SymbolNode { .. } => "SymbolNode",
+        prism::Node::TrueNode { .. } => "TrueNode",
+        prism::Node::UndefNode { .. } => "UndefNode",
+        prism::Node::UnlessNode { .. } => "UnlessNode",
+        prism::Node::UntilNode { .. } => "UntilNode",
+        prism::Node::WhenNode { .. } => "WhenNode",
+        prism::Node::WhileNode { .. } => "WhileNode",
+        prism::Node::XStringNode { .. } => "XStringNode",
+        prism::Node::YieldNode { .. } => "YieldNode",
+    }
+}
@@ -22,44 +22,95 @@ mod strings;
 mod symbols;
 mod trivia;
 
-use crate::{error::AppError, fmt};
+use crate::{
+    error::{AppError, Diagnostic, Severity, SourceLocation},
+    fmt,
+};
 use log::debug;
-use std::{collections::HashMap, iter::Peekable};
+use prism::Visit;
+use std::{collections::HashMap, iter::Peekable, ops::Range};
 
 pub(crate) fn parse_from_prism_result(
     result: prism::ParseResult,
 ) -> Result<ParserResult, AppError> {
-    let messages = result
+    let diagnostics = result
         .errors()
-        .map(|e| {
-            let loc = e.location();
-            format!(
-                "[{},{}] {}",
-                loc.start_offset(),
-                loc.end_offset(),
-                e.message()
-            )
-        })
+        .map(|e| diagnostic_of(&result, Severity::Error, e))
         .collect::<Vec<_>>();
-    if !messages.is_empty() {
-        return Err(AppError::ParseFailed(messages));
+    if !diagnostics.is_empty() {
+        return Err(AppError::ParseFailed(diagnostics));
     }
 
+    // The data section after `__END__` is not a part of the program.
+    let (program_src, data_section) = match result.data_loc() {
+        Some(loc) => {
+            let (program, data) = result.source().split_at(loc.start_offset());
+            (program, Some(data.to_vec()))
+        }
+        None => (result.source(), None),
+    };
     let comments = result.comments().peekable();
-    let mut parser = Parser::new(result.source(), comments);
+    let mut parser = Parser::new(program_src, comments);
     let fmt_node = parser.parse_from_prism_node(result.node());
+    if let Some(unsupported) = parser.unsupported {
+        return Err(AppError::Unsupported {
+            node_kind: unsupported.node_kind,
+            location: SourceLocation::new(result.source(), unsupported.start),
+        });
+    }
     debug!("fmt node: {:#?}", &fmt_node);
     debug!("heredoc map: {:#?}", &parser.heredoc_map);
+    let verbatim_nodes = parser
+        .verbatim_nodes
+        .iter()
+        .map(|node| {
+            let message = format!("{} is left as it is", node.node_kind);
+            Diagnostic::new(
+                Severity::Note,
+                result.source(),
+                node.start,
+                node.end,
+                message,
+            )
+        })
+        .collect();
     Ok(ParserResult {
         node: fmt_node,
         heredoc_map: parser.heredoc_map,
+        verbatim_nodes,
+        data_section,
     })
 }
 
+pub(crate) fn warnings_of(result: &prism::ParseResult) -> Vec<Diagnostic> {
+    result
+        .warnings()
+        .map(|w| diagnostic_of(result, Severity::Warning, w))
+        .collect()
+}
+
+pub(crate) fn diagnostic_of(
+    result: &prism::ParseResult,
+    severity: Severity,
+    diagnostic: prism::Diagnostic,
+) -> Diagnostic {
+    let loc = diagnostic.location();
+    Diagnostic::new(
+        severity,
+        result.source(),
+        loc.start_offset(),
+        loc.end_offset(),
+        diagnostic.message().to_string(),
+    )
+}
+
 #[derive(Debug)]
 pub(crate) struct ParserResult {
     pub node: fmt::Node,
     pub heredoc_map: fmt::HeredocMap,
+    pub verbatim_nodes: Vec<Diagnostic>,
+    /// The `__END__` line and the data after it, kept as they are.
+    pub data_section: Option<Vec<u8>>,
 }
 
 struct Parser<'src> {
@@ -69,6 +120,16 @@ struct Parser<'src> {
     position_gen: usize,
     last_loc_end: usize,
     last_heredoc_end: usize,
+    unsupported: Option<UnsupportedNode>,
+    verbatim_nodes: Vec<UnsupportedNode>,
+}
+
+// A node the parser could not handle.
+#[derive(Debug)]
+struct UnsupportedNode {
+    node_kind: String,
+    start: usize,
+    end: usize,
 }
 
 impl Parser<'_> {
@@ -80,6 +141,8 @@ impl Parser<'_> {
             position_gen: 0,
             last_loc_end: 0,
             last_heredoc_end: 0,
+            unsupported: None,
+            verbatim_nodes: vec![],
         }
     }
 
@@ -159,6 +222,7 @@ impl Parser<'_> {
             prism::Node::StringNode { .. } => {
                 let node = node.as_string_node().unwrap();
                 self.parse_string_or_heredoc(
+                    node.as_node(),
                     node.opening_loc(),
                     node.content_loc(),
                     node.closing_loc(),
@@ -167,6 +231,7 @@ impl Parser<'_> {
             prism::Node::InterpolatedStringNode { .. } => {
                 let node = node.as_interpolated_string_node().unwrap();
                 self.parse_interpolated_string_or_heredoc(
+                    node.as_node(),
                     node.opening_loc(),
                     node.parts(),
                     node.closing_loc(),
@@ -176,6 +241,7 @@ impl Parser<'_> {
             prism::Node::XStringNode { .. } => {
                 let node = node.as_x_string_node().unwrap();
                 self.parse_string_or_heredoc(
+                    node.as_node(),
                     Some(node.opening_loc()),
                     node.content_loc(),
                     Some(node.closing_loc()),
@@ -184,6 +250,7 @@ impl Parser<'_> {
             prism::Node::InterpolatedXStringNode { .. } => {
                 let node = node.as_interpolated_x_string_node().unwrap();
                 self.parse_interpolated_string_or_heredoc(
+                    node.as_node(),
                     Some(node.opening_loc()),
                     node.parts(),
                     Some(node.closing_loc()),
@@ -655,10 +722,94 @@ impl Parser<'_> {
                 self.parse_alias(node.new_name(), node.old_name())
             }
 
-            _ => unreachable!("prism node not handled: {:?}", node),
+            _ => self.parse_unsupported(node),
         }
     }
 
+    // Keep the node we cannot format as it is so that we can still format the rest of the code.
+    fn parse_unsupported(&mut self, node: prism::Node) -> fmt::Node {
+        let loc = node.location();
+        let start = loc.start_offset();
+        self.verbatim_nodes.push(UnsupportedNode {
+            node_kind: node_kind_name(&node),
+            start,
+            end: loc.end_offset(),
+        });
+
+        let mut literals = LiteralCollector::default();
+        literals.visit(&node);
+
+        // The bodies of the heredocs opened in the node follow it, so they are kept as well.
+        let mut end = loc.end_offset();
+        if let Some(heredoc_end) = literals.heredoc_end.filter(|e| *e > end) {
+            let line_end = self.src[end..]
+                .iter()
+                .position(|b| *b == b'\n')
+                .map_or(self.src.len(), |i| end + i);
+            let rest = String::from_utf8_lossy(&self.src[end..line_end]);
+            let rest = rest.trim();
+            if !rest.is_empty() && !rest.starts_with('#') {
+                // Other code on the same line would be written twice.
+                self.mark_as_unsupported(&node);
+            }
+            end = heredoc_end;
+            while end > 0 && matches!(self.src[end - 1], b'\n' | b'\r') {
+                end -= 1;
+            }
+            self.last_heredoc_end = end;
+        }
+
+        // The comments inside the node are written as a part of the verbatim source.
+        while let Some(comment) = self.comments.peek() {
+            let comment_loc = comment.location();
+            if comment_loc.start_offset() >= end {
+                break;
+            }
+            if comment.text().starts_with(b"=begin") {
+                // Include the line break before it so that the `=begin` line is kept as well.
+                let range = comment_loc.start_offset() - 1..comment_loc.end_offset();
+                literals.ranges.push(range);
+            }
+            self.comments.next();
+        }
+
+        let literal_ranges = literals
+            .ranges
+            .iter()
+            .map(|r| r.start.saturating_sub(start)..r.end.saturating_sub(start))
+            .collect::<Vec<_>>();
+        let base_indent = self.indent_of_line_at(start);
+        let verbatim = fmt::Verbatim::new(&self.src[start..end], base_indent, &literal_ranges);
+        fmt::Node::new(fmt::Kind::Verbatim(verbatim))
+    }
+
+    // Record the node we cannot handle instead of panicking so that
+    // the caller can report it as an error.
+    // This is used where a node cannot be replaced with a verbatim one.
+    fn mark_as_unsupported(&mut self, node: &prism::Node) {
+        if self.unsupported.is_none() {
+            let loc = node.location();
+            self.unsupported = Some(UnsupportedNode {
+                node_kind: node_kind_name(node),
+                start: loc.start_offset(),
+                end: loc.end_offset(),
+            });
+        }
+    }
+
+    // Returns the leading spaces and tabs of the line containing the offset.
+    fn indent_of_line_at(&self, offset: usize) -> String {
+        let line_start = self.src[..offset]
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+        let len = self.src[line_start..]
+            .iter()
+            .take_while(|b| **b == b' ' || **b == b'\t')
+            .count();
+        String::from_utf8_lossy(&self.src[line_start..line_start + len]).to_string()
+    }
+
     fn next_pos(&mut self) -> fmt::Pos {
         self.position_gen += 1;
         fmt::Pos(self.position_gen)
@@ -710,3 +861,68 @@ impl Parser<'_> {
         false
     }
 }
+
+// Collects the source ranges of string-like literals in a node, where line breaks and
+// the indentation after them are a part of the values.
+#[derive(Debug, Default)]
+struct LiteralCollector {
+    ranges: Vec<Range<usize>>,
+    /// The end of the last heredoc closing.
+    heredoc_end: Option<usize>,
+}
+
+impl LiteralCollector {
+    fn collect(&mut self, node: &prism::Node) {
+        let (opening, closing) = match node {
+            prism::Node::StringNode { .. } => {
+                let node = node.as_string_node().unwrap();
+                (node.opening_loc(), node.closing_loc())
+            }
+            prism::Node::InterpolatedStringNode { .. } => {
+                let node = node.as_interpolated_string_node().unwrap();
+                (node.opening_loc(), node.closing_loc())
+            }
+            prism::Node::XStringNode { .. } => {
+                let node = node.as_x_string_node().unwrap();
+                (Some(node.opening_loc()), Some(node.closing_loc()))
+            }
+            prism::Node::InterpolatedXStringNode { .. } => {
+                let node = node.as_interpolated_x_string_node().unwrap();
+                (Some(node.opening_loc()), Some(node.closing_loc()))
+            }
+            prism::Node::SymbolNode { .. }
+            | prism::Node::InterpolatedSymbolNode { .. }
+            | prism::Node::RegularExpressionNode { .. }
+            | prism::Node::InterpolatedRegularExpressionNode { .. }
+            | prism::Node::MatchLastLineNode { .. }
+            | prism::Node::InterpolatedMatchLastLineNode { .. } => (None, None),
+            _ => return,
+        };
+        match (opening, closing) {
+            (Some(opening), Some(closing)) if opening.as_slice().starts_with(b"<<") => {
+                // The heredoc body starts at the line after the opening.
+                self.ranges.push(opening.end_offset()..closing.end_offset());
+                self.heredoc_end = self.heredoc_end.max(Some(closing.end_offset()));
+            }
+            _ => {
+                let loc = node.location();
+                self.ranges.push(loc.start_offset()..loc.end_offset());
+            }
+        }
+    }
+}
+
+impl<'pr> prism::Visit<'pr> for LiteralCollector {
+    fn visit_branch_node_enter(&mut self, node: prism::Node<'pr>) {
+        self.collect(&node);
+    }
+
+    fn visit_leaf_node_enter(&mut self, node: prism::Node<'pr>) {
+        self.collect(&node);
+    }
+}
+
+// The node type name like "CallNode", to tell which syntax is unsupported.
+fn node_kind_name(node: &prism::Node) -> String {
+    crate::meaning::node_name(node).to_string()
+}
@@ -17,7 +17,11 @@ impl<'src> super::Parser<'src> {
                     let node = body.as_begin_node().unwrap();
                     self.parse_begin_body(node)
                 }
-                _ => panic!("unexpected def body: {:?}", body),
+                _ => {
+                    self.mark_as_unsupported(&body);
+                    let statements = self.wrap_as_statements(None, trailing_end);
+                    fmt::BlockBody::new(statements)
+                }
             },
             None => {
                 let statements = self.wrap_as_statements(None, trailing_end);
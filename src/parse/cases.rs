@@ -39,7 +39,10 @@ impl<'src> super::Parser<'src> {
                         let node = node.as_when_node().unwrap();
                         self.parse_case_when(node, trailing_end)
                     }
-                    _ => panic!("unexpected case expression branch: {:?}", node),
+                    _ => {
+                        self.mark_as_unsupported(&node);
+                        return;
+                    }
                 };
                 branches.push(condition);
             },
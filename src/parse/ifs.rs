@@ -23,8 +23,7 @@ impl<'src> super::Parser<'src> {
                 end_loc: node.end_keyword_loc(),
             })
         } else if node.then_keyword_loc().map(|l| l.as_slice()) == Some(b"?") {
-            let ternary = self.visit_ternary(node);
-            fmt::Node::new(fmt::Kind::Ternary(ternary))
+            self.parse_ternary(node)
         } else {
             self.parse_postmodifier(postmodifiers::Postmodifier {
                 keyword: "if".to_string(),
@@ -136,36 +135,30 @@ impl<'src> super::Parser<'src> {
                 let if_last = self.parse_else(node, end_loc.start_offset());
                 ifexpr.if_last = Some(if_last);
             }
-            _ => {
-                panic!("unexpected node in IfNode: {:?}", node);
-            }
+            _ => self.mark_as_unsupported(&node),
         }
     }
 
-    fn visit_ternary(&mut self, node: prism::IfNode) -> fmt::Ternary {
+    fn parse_ternary(&mut self, node: prism::IfNode) -> fmt::Node {
+        let Some(consequent) = node.consequent().and_then(|c| c.as_else_node()) else {
+            self.mark_as_unsupported(&node.as_node());
+            return self.parse_unsupported(node.as_node());
+        };
         let question_loc = node.then_keyword_loc().expect("ternary if must have ?");
         let predicate = self.parse(node.predicate(), Some(question_loc.start_offset()));
         let then = node
             .statements()
             .and_then(|s| s.body().iter().next())
             .expect("ternary if must have then statement");
-        match node.consequent() {
-            Some(consequent) => match consequent {
-                prism::Node::ElseNode { .. } => {
-                    let consequent = consequent.as_else_node().unwrap();
-                    let otherwise = consequent
-                        .statements()
-                        .and_then(|s| s.body().iter().next())
-                        .expect("ternary if must have else statement");
-                    let pred_trailing = self.take_trailing_comment(then.location().start_offset());
-                    let loc = consequent.location();
-                    let then = self.parse(then, Some(loc.start_offset()));
-                    let otherwise = self.parse(otherwise, None);
-                    fmt::Ternary::new(predicate, pred_trailing, then, otherwise)
-                }
-                _ => panic!("ternary if consequent must be ElseNode: {:?}", node),
-            },
-            _ => panic!("ternary if must have consequent"),
-        }
+        let otherwise = consequent
+            .statements()
+            .and_then(|s| s.body().iter().next())
+            .expect("ternary if must have else statement");
+        let pred_trailing = self.take_trailing_comment(then.location().start_offset());
+        let loc = consequent.location();
+        let then = self.parse(then, Some(loc.start_offset()));
+        let otherwise = self.parse(otherwise, None);
+        let ternary = fmt::Ternary::new(predicate, pred_trailing, then, otherwise);
+        fmt::Node::new(fmt::Kind::Ternary(ternary))
     }
 }
@@ -62,7 +62,11 @@ impl<'src> super::Parser<'src> {
                     );
                     (args, None)
                 }
-                _ => panic!("unexpected block node of call: {:?}", node),
+                _ => {
+                    self.mark_as_unsupported(&node);
+                    let args = self.parse_arguments(arguments_iter, None, opening_loc, closing_loc);
+                    (args, None)
+                }
             },
             // method call without block (e.g. "foo", "foo(a)")
             None => {
@@ -217,9 +221,10 @@ impl<'src> super::Parser<'src> {
     }
 
     fn parse_index_write_call(&mut self, call: prism::CallNode) -> fmt::Node {
-        let (opening_loc, closing_loc) = match (call.opening_loc(), call.closing_loc()) {
-            (Some(op), Some(cl)) => (op, cl),
-            _ => panic!("index write must have opening and closing"),
+        let (Some(opening_loc), Some(closing_loc)) = (call.opening_loc(), call.closing_loc())
+        else {
+            self.mark_as_unsupported(&call.as_node());
+            return self.parse_unsupported(call.as_node());
         };
 
         let receiver = call.receiver().expect("index write must have receiver");
@@ -398,7 +403,7 @@ impl<'src> super::Parser<'src> {
                 }
                 prism::Node::NumberedParametersNode { .. } => {}
                 prism::Node::ItParametersNode { .. } => {}
-                _ => panic!("unexpected node for call block params: {:?}", node),
+                _ => self.mark_as_unsupported(&params),
             }
         }
 
@@ -85,7 +85,10 @@ impl<'src> super::Parser<'src> {
                 Some(self.parse_block_parameters(params, params_end))
             }
             prism::Node::NumberedParametersNode { .. } => None,
-            _ => panic!("unexpected node for lambda params: {:?}", node),
+            _ => {
+                self.mark_as_unsupported(&params);
+                None
+            }
         });
 
         let body_end = node.closing_loc().start_offset();
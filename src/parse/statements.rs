@@ -19,8 +19,17 @@ impl<'src> super::Parser<'src> {
         let mut statements = fmt::Statements::new();
         if let Some(node) = node {
             Self::each_node_with_trailing_end(node.body().iter(), end, |node, trailing_end| {
+                let loc_end = node.location().end_offset();
+                let span_start = self.leading_comment_start(node.location().start_offset());
+                let last_heredoc_end = self.last_heredoc_end;
                 let fmt_node = self.parse(node, trailing_end);
-                statements.append_node(fmt_node);
+                // The heredoc bodies follow the node so they are in its span as well.
+                let span_end = if self.last_heredoc_end != last_heredoc_end {
+                    loc_end.max(self.last_heredoc_end + 1)
+                } else {
+                    loc_end
+                };
+                statements.append_node_at(fmt_node, span_start..span_end);
             });
         }
         let virtual_end = self.take_end_trivia_as_virtual_end(end);
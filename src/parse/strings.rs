@@ -3,11 +3,16 @@ use crate::fmt;
 impl<'src> super::Parser<'src> {
     pub(super) fn parse_string_or_heredoc(
         &mut self,
+        node: prism::Node,
         opening_loc: Option<prism::Location>,
         content_loc: prism::Location,
         closing_loc: Option<prism::Location>,
     ) -> fmt::Node {
         let kind = if is_heredoc(opening_loc.as_ref()) {
+            let Some(closing_loc) = closing_loc else {
+                self.mark_as_unsupported(&node);
+                return self.parse_unsupported(node);
+            };
             let opening = self.parse_heredoc(opening_loc, content_loc, closing_loc);
             fmt::Kind::HeredocOpening(opening)
         } else {
@@ -19,11 +24,16 @@ impl<'src> super::Parser<'src> {
 
     pub(super) fn parse_interpolated_string_or_heredoc(
         &mut self,
+        node: prism::Node,
         opening_loc: Option<prism::Location>,
         parts: prism::NodeList,
         closing_loc: Option<prism::Location>,
     ) -> fmt::Node {
         let kind = if is_heredoc(opening_loc.as_ref()) {
+            let Some(closing_loc) = closing_loc else {
+                self.mark_as_unsupported(&node);
+                return self.parse_unsupported(node);
+            };
             let opening = self.parse_interpolated_heredoc(opening_loc, parts, closing_loc);
             fmt::Kind::HeredocOpening(opening)
         } else {
@@ -96,7 +106,7 @@ impl<'src> super::Parser<'src> {
                     let embedded_var = fmt::EmbeddedVariable::new(operator, variable);
                     dstr.append_part(fmt::DynStrPart::Variable(embedded_var));
                 }
-                _ => panic!("unexpected string interpolation node: {:?}", part),
+                _ => self.mark_as_unsupported(&part),
             }
         }
         dstr
@@ -106,15 +116,14 @@ impl<'src> super::Parser<'src> {
         &mut self,
         opening_loc: Option<prism::Location>,
         content_loc: prism::Location,
-        closing_loc: Option<prism::Location>,
+        closing_loc: prism::Location,
     ) -> fmt::HeredocOpening {
         let open = opening_loc.as_ref().unwrap().as_slice();
         let (indent_mode, id) = fmt::HeredocIndentMode::parse_mode_and_id(open);
         let opening_id = String::from_utf8_lossy(id).to_string();
-        let closing_loc = closing_loc.expect("heredoc must have closing");
         let closing_id = Self::source_lossy_at(&closing_loc)
             .trim_start()
-            .trim_end_matches('\n')
+            .trim_end_matches(['\r', '\n'])
             .to_string();
         let str = self.parse_string(None, content_loc, None);
         let heredoc = fmt::Heredoc {
@@ -131,7 +140,7 @@ impl<'src> super::Parser<'src> {
         &mut self,
         opening_loc: Option<prism::Location>,
         content_parts: prism::NodeList,
-        closing_loc: Option<prism::Location>,
+        closing_loc: prism::Location,
     ) -> fmt::HeredocOpening {
         let open = opening_loc.unwrap().as_slice();
         let (indent_mode, id) = fmt::HeredocIndentMode::parse_mode_and_id(open);
@@ -187,13 +196,12 @@ impl<'src> super::Parser<'src> {
                     self.last_loc_end = loc.end_offset();
                     last_part_end = Some(loc.end_offset());
                 }
-                _ => panic!("unexpected heredoc part: {:?}", part),
+                _ => self.mark_as_unsupported(&part),
             }
         }
-        let closing_loc = closing_loc.expect("heredoc must have closing");
         let closing_id = Self::source_lossy_at(&closing_loc)
             .trim_start()
-            .trim_end_matches('\n')
+            .trim_end_matches(['\r', '\n'])
             .to_string();
         let heredoc = fmt::Heredoc {
             id: closing_id,
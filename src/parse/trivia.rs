@@ -30,7 +30,7 @@ impl<'src> super::Parser<'src> {
                 value = value.trim_end().to_string();
                 fmt::Comment::Block(value)
             } else {
-                fmt::Comment::Oneline(value)
+                fmt::Comment::Oneline(trim_carriage_return(value))
             };
             self.take_empty_lines_until(last_end, loc.start_offset(), &mut trivia);
             trivia.append_line(fmt::LineTrivia::Comment(comment));
@@ -43,6 +43,20 @@ impl<'src> super::Parser<'src> {
         trivia
     }
 
+    // Returns the start of the first comment taken as the leading trivia of the node starting at
+    // the given offset, or the offset itself if there is no such comment.
+    pub(super) fn leading_comment_start(&mut self, loc_start: usize) -> usize {
+        let last_end = self.determine_actual_last_end(loc_start);
+        match self.comments.peek() {
+            Some(comment)
+                if (last_end..=loc_start).contains(&comment.location().start_offset()) =>
+            {
+                comment.location().start_offset()
+            }
+            _ => loc_start,
+        }
+    }
+
     fn determine_actual_last_end(&self, base: usize) -> usize {
         if self.last_loc_end < self.last_heredoc_end && self.last_heredoc_end < base {
             self.last_heredoc_end
@@ -73,7 +87,7 @@ impl<'src> super::Parser<'src> {
                 self.last_loc_end = loc.end_offset() - 1;
                 self.comments.next();
                 let value = Self::source_lossy_at(&loc);
-                return fmt::TrailingTrivia::new(Some(value));
+                return fmt::TrailingTrivia::new(Some(trim_carriage_return(value)));
             }
         };
         fmt::TrailingTrivia::none()
@@ -91,7 +105,7 @@ impl<'src> super::Parser<'src> {
                     line_start = Some(i);
                     break;
                 }
-            } else if line_end.is_some() && b != b' ' {
+            } else if line_end.is_some() && !matches!(b, b' ' | b'\t' | b'\r') {
                 line_end = None;
             }
         }
@@ -109,7 +123,10 @@ impl<'src> super::Parser<'src> {
         let mut has_char_between_last_newline = false;
         while let Some(b) = self.src.get(idx) {
             match b {
-                b' ' => {
+                b' ' | b'\t' | b'\r' => {
+                    if idx == 0 {
+                        break;
+                    }
                     idx -= 1;
                     continue;
                 }
@@ -123,3 +140,11 @@ impl<'src> super::Parser<'src> {
         !has_char_between_last_newline
     }
 }
+
+// A comment on a CRLF line ends with the carriage return, which the output writes by itself.
+fn trim_carriage_return(comment: String) -> String {
+    match comment.strip_suffix('\r') {
+        Some(comment) => comment.to_string(),
+        None => comment,
+    }
+}
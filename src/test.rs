@@ -7,6 +7,37 @@ use std::{
     path::{Path, PathBuf},
 };
 
+/// A temporary directory with files for tests, removed when dropped.
+/// It has empty `.rbfmt.yml` and `.editorconfig` with `root = true` unless the files are given,
+/// so that the config files outside of it never affect the tests.
+pub(crate) struct TempFiles {
+    pub root: PathBuf,
+}
+
+impl TempFiles {
+    pub(crate) fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let root = env::temp_dir().join(format!("rbfmt-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let defaults = [(".rbfmt.yml", ""), (".editorconfig", "root = true\n")];
+        for (path, content) in defaults.iter().chain(files) {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        Self { root }
+    }
+
+    pub(crate) fn path(&self, path: &str) -> PathBuf {
+        self.root.join(path)
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
 #[test]
 fn system_tests() {
     let dirs = get_test_dirs(Path::new("tests"));
@@ -41,10 +72,218 @@ fn compare_files(dir_path: PathBuf) {
     output_path.push("out.rb");
 
     let input = fs::read(&input_path).unwrap();
-    let want = fs::read_to_string(&output_path).unwrap();
+    let want = fs::read(&output_path).unwrap();
     let config = crate::config::FormatConfig::default();
-    match crate::format_source(input, config) {
-        Ok(got) => assert_eq!(want, got.code, "{:?}", &input_path),
+    match crate::format_source(input, config, crate::FormatOptions::default()) {
+        Ok(got) => {
+            // Compare as strings first to show a readable diff.
+            assert_eq!(
+                String::from_utf8_lossy(&want),
+                String::from_utf8_lossy(&got.code),
+                "{:?}",
+                &input_path
+            );
+            assert!(want == got.code, "{:?}", &input_path);
+        }
         Err(err) => unreachable!("test file could not be formatted: {:?}", err),
     }
 }
+
+#[test]
+fn format_only_statements_on_lines() {
+    let cases = [
+        (
+            "a  =  1\nb  =  2\nc  =  3\n",
+            vec![2..=2],
+            "a  =  1\nb = 2\nc  =  3\n",
+        ),
+        // Statements are formatted at their current indentation.
+        (
+            "def foo\n    x  =  1\n    y  =  2\nend\nz  =  3\n",
+            vec![3..=3],
+            "def foo\n    x  =  1\n    y = 2\nend\nz  =  3\n",
+        ),
+        // The lines grow to the whole statement enclosing them.
+        (
+            "foo(\n  1,2,\n  3)\nb  =  2\n",
+            vec![2..=2],
+            "foo(1, 2, 3)\nb  =  2\n",
+        ),
+        // Statements sharing a line are formatted together.
+        (
+            "a  =  1;  b  =  2\nc  =  3",
+            vec![1..=1],
+            "a = 1\nb = 2\nc  =  3",
+        ),
+        // Each range is formatted without moving the lines of the others.
+        (
+            "a  =  1\nfoo(\n  1,2,\n  3)\nc  =  3\nd  =  4\n",
+            vec![3..=3, 5..=5],
+            "a  =  1\nfoo(1, 2, 3)\nc = 3\nd  =  4\n",
+        ),
+        // Overlapping ranges are formatted as one.
+        (
+            "a  =  1\nfoo(\n  1,2,\n  3)\nc  =  3\nd  =  4\n",
+            vec![3..=5, 4..=4],
+            "a  =  1\nfoo(1, 2, 3)\nc = 3\nd  =  4\n",
+        ),
+        // Lines without statements are kept as they are.
+        (
+            "a  =  1\n\n\nb  =  2 # foo\n# bar\n",
+            vec![2..=3, 5..=5],
+            "a  =  1\n\n\nb  =  2 # foo\n# bar\n",
+        ),
+    ];
+    let options = crate::FormatOptions {
+        verify: true,
+        verify_idempotent: true,
+        ..Default::default()
+    };
+    for (input, lines, want) in cases {
+        let config = crate::config::FormatConfig::default();
+        let got = crate::format_lines(input.as_bytes().to_vec(), config, options, &lines);
+        match got {
+            Ok(got) => assert_eq!(want, String::from_utf8_lossy(&got.code), "{lines:?}"),
+            Err(err) => unreachable!("lines {lines:?} could not be formatted: {err}"),
+        }
+    }
+}
+
+#[test]
+fn verify_idempotency_of_formatted_lines() {
+    // The second pass formats the range of the first output, which is shorter than the target lines.
+    let input = "a  =  1\nfoo(\n  1,2,\n  3)\nb  =  2\n";
+    let options = crate::FormatOptions {
+        verify_idempotent: true,
+        ..Default::default()
+    };
+    let config = crate::config::FormatConfig::default();
+    let got = crate::format_lines(input.as_bytes().to_vec(), config, options, &[2..=4]);
+    match got {
+        Ok(got) => assert_eq!(
+            "a  =  1\nfoo(1, 2, 3)\nb  =  2\n",
+            String::from_utf8_lossy(&got.code)
+        ),
+        Err(err) => unreachable!("lines could not be formatted: {err}"),
+    }
+}
+
+#[test]
+fn format_with_indentation_config() {
+    use crate::config::{FormatConfig, IndentStyle};
+
+    let spaces = FormatConfig {
+        indent_size: 4,
+        ..Default::default()
+    };
+    let tabs = FormatConfig {
+        indent_style: IndentStyle::Tabs,
+        ..Default::default()
+    };
+    let tabs_verbatim = FormatConfig {
+        indent_style: IndentStyle::Tabs,
+        tab_width: Some(4),
+        ..Default::default()
+    };
+    // A tab counts as 8 columns so the call does not fit in the line.
+    let wide_tabs = FormatConfig {
+        line_width: 20,
+        indent_style: IndentStyle::Tabs,
+        tab_width: Some(8),
+        ..Default::default()
+    };
+    let cases = [
+        (
+            spaces,
+            "class A\ndef foo\nbar\nend\nend\n",
+            "class A\n    def foo\n        bar\n    end\nend\n",
+        ),
+        (
+            tabs,
+            "class A\ndef foo\n<<~X\n  a\nX\nend\nend\n",
+            "class A\n\tdef foo\n\t\t<<~X\n\t\t\ta\n\t\tX\n\tend\nend\n",
+        ),
+        // Unsupported nodes keep their tab indentation, and the lines indented by spaces in them
+        // are shifted by the width of the tab at the start of the node.
+        (
+            tabs_verbatim,
+            "class A\n\t# shareable_constant_value: literal\n\tB = [\n      1,\n\t]\ndef foo\n<<~X\n    a\n      b\nX\nend\nend\n",
+            "class A\n\t# shareable_constant_value: literal\n\tB = [\n\t  1,\n\t]\n\tdef foo\n\t\t<<~X\n\t\t\ta\n\t\t\t  b\n\t\tX\n\tend\nend\n",
+        ),
+        (
+            wide_tabs,
+            "def foo\nbar(1111, 2222)\nend\n",
+            "def foo\n\tbar(\n\t\t1111,\n\t\t2222\n\t)\nend\n",
+        ),
+    ];
+    let options = crate::FormatOptions {
+        verify: true,
+        ..Default::default()
+    };
+    for (config, input, want) in cases {
+        let got = crate::format_source(input.as_bytes().to_vec(), config, options);
+        match got {
+            Ok(got) => assert_eq!(want, String::from_utf8_lossy(&got.code)),
+            Err(err) => unreachable!("{input:?} could not be formatted: {err}"),
+        }
+    }
+}
+
+#[test]
+fn format_with_line_ending_config() {
+    use crate::config::{FormatConfig, LineEnding};
+
+    let crlf_source = "a  =  1 # c\r\n\r\n# d\r\nfoo(<<~X)\r\n  x\r\n\r\n  y\r\nX\r\n";
+    let config = |line_ending| FormatConfig {
+        line_ending,
+        ..Default::default()
+    };
+    let cases = [
+        (
+            config(LineEnding::Auto),
+            crlf_source,
+            "a = 1 # c\r\n\r\n# d\r\nfoo(<<~X)\r\n  x\r\n\r\n  y\r\nX\r\n",
+        ),
+        (
+            config(LineEnding::Lf),
+            crlf_source,
+            "a = 1 # c\n\n# d\nfoo(<<~X)\n  x\n\n  y\nX\n",
+        ),
+        (
+            config(LineEnding::Crlf),
+            "if a\nb\nend\n",
+            "if a\r\n  b\r\nend\r\n",
+        ),
+    ];
+    let options = crate::FormatOptions {
+        verify: true,
+        verify_idempotent: true,
+        ..Default::default()
+    };
+    for (config, input, want) in cases {
+        let got = crate::format_source(input.as_bytes().to_vec(), config, options);
+        match got {
+            Ok(got) => assert_eq!(want, String::from_utf8_lossy(&got.code)),
+            Err(err) => unreachable!("{input:?} could not be formatted: {err}"),
+        }
+    }
+}
+
+#[test]
+fn reject_unparsable_output() {
+    let err = match crate::parse_output(b"foo(") {
+        Err(err @ crate::AppError::BrokenOutput(_)) => err,
+        Err(err) => unreachable!("unexpected error: {err}"),
+        Ok(_) => unreachable!("broken output must be rejected"),
+    };
+    assert_eq!(
+        err.to_string(),
+        "\
+formatted code has a syntax error (formatter bug)
+error: unexpected end-of-input; expected a `)` to close the arguments
+ --> <formatted>:1:5
+  |
+1 | foo(
+  |     ^"
+    );
+}
@@ -0,0 +1,3 @@
+__END__
+ # not a comment
+=begin
@@ -0,0 +1,38 @@
+# shareable_constant_value: literal
+A = [
+    1,
+      2, # two
+]
+foo(  1)
+
+class B
+    # shareable_constant_value: literal
+    C = "x
+    y"
+    D = [
+        1,
+=begin
+  doc
+=end
+        <<~X,
+          a
+        X
+        /a
+          b/x,
+    ]
+end
+
+class E
+# shareable_constant_value: literal
+F = [
+=begin
+=end
+  1,
+]
+end
+
+# shareable_constant_value: literal
+G = <<~X # g
+  a
+X
+foo(  2)